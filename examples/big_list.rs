@@ -0,0 +1,27 @@
+extern crate orbtk;
+
+use orbtk::{ Window, List, Rect, Entry, Label };
+use orbtk::traits::{ Place, Text, Click };
+
+fn main() {
+    let mut window = Window::new(Rect::new(100, 100, 420, 420), "OrbTK");
+
+    // Rows are only built when they are scrolled into view
+    let list = List::new();
+    list.position(5, 5).size(400, 400);
+    list.model(100000, 24, |_, i| {
+        let label = Label::new();
+        label.text(format!("Log line {}", i)).position(4, 4).size(392, 16);
+
+        let entry = Entry::new(24);
+        entry.on_click(move |_, _| { println!("{}", i); });
+        entry.add(&label);
+        entry
+    });
+    // Typing searches the rows by their text, without building them
+    list.model_text(|_, i| format!("Log line {}", i));
+
+    window.add(&list);
+
+    window.exec();
+}
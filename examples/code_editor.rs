@@ -0,0 +1,24 @@
+extern crate orbtk;
+
+use orbtk::{CodeEditor, FindBar, Rect, Window};
+use orbtk::highlight::TomlTokenizer;
+use orbtk::traits::{Place, Text};
+
+fn main() {
+    let mut window = Window::new(Rect::new(100, 100, 620, 452), "Code Editor");
+
+    let editor = CodeEditor::new();
+    editor.tokenizer(TomlTokenizer)
+        .tab_width(4)
+        .position(10, 10)
+        .size(600, 400);
+    editor.text("[package]\nname = \"orbtk\"\nversion = \"0.2.25\"\n\n# Dependencies\n[dependencies]\norbclient = { version = \"0.3.11\" }\n");
+    window.add(&editor);
+
+    let find_bar = FindBar::new(&editor.text_box);
+    find_bar.position(0, 420)
+        .size(620, 32);
+    window.add(&find_bar);
+
+    window.exec();
+}
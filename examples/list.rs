@@ -1,6 +1,6 @@
 extern crate orbtk;
 
-use orbtk::{ Window, List, Rect, Entry, Button };
+use orbtk::{ Window, List, Rect, Entry, Button, SelectionMode, Menu, Action, Point };
 use orbtk::traits::{ Place, Text, Click };
 
 fn main() {
@@ -8,6 +8,9 @@ fn main() {
 
     let list = List::new();
     list.position(5, 5).size(400, 400);
+    list.selection_mode(SelectionMode::Extended);
+    list.on_selection_changed(|list| { println!("selected {:?}", list.selected_indices()); });
+    list.reorderable(true).on_reorder(|_, from, to| { println!("moved {} to {}", from, to); });
 
     for i in 0..10 {
         let button = Button::new();
@@ -22,5 +25,41 @@ fn main() {
 
     window.add(&list);
 
+    let remove = Button::new();
+    remove.text("Remove").position(5, 415).size(80, 30).text_offset(16, 7);
+    {
+        let list = list.clone();
+        remove.on_click(move |_, _| {
+            for i in list.selected_indices().into_iter().rev() {
+                list.remove(i);
+            }
+        });
+    }
+    window.add(&remove);
+
+    // Right clicking the list opens a menu
+    let context = Menu::new("");
+    context.size(0, 24).text_offset(8, 4);
+    {
+        let action = Action::new("Select All");
+        action.text_offset(8, 4);
+        let list = list.clone();
+        action.on_click(move |_: &Action, _: Point| list.select_all());
+        context.add(&action);
+    }
+    {
+        let action = Action::new("Remove");
+        action.text_offset(8, 4);
+        let list = list.clone();
+        action.on_click(move |_: &Action, _: Point| {
+            for i in list.selected_indices().into_iter().rev() {
+                list.remove(i);
+            }
+        });
+        context.add(&action);
+    }
+    context.context_for(&list);
+    window.add(&context);
+
     window.exec();
 }
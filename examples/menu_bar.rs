@@ -0,0 +1,78 @@
+extern crate orbtk;
+
+use orbtk::{Window, Rect, Point, Label, Menu, MenuBar, Action, Separator};
+use orbtk::traits::{Click, Place, Text};
+
+fn main() {
+    let mut window = Window::new(Rect::new(100, 100, 420, 300), "Menu Bar");
+
+    let label = Label::new();
+    label.position(10, 40).size(400, 16).text("Press Alt+F to open the file menu");
+    window.add(&label);
+
+    let action = |menu: &Menu, text: &str, accelerator: &str| {
+        let action = Action::new(text);
+        if !accelerator.is_empty() {
+            if let Err(err) = action.accelerator(accelerator) {
+                println!("{}", err);
+            }
+        }
+        let label = label.clone();
+        let chosen = text.replace("&", "");
+        action.on_click(move |_action: &Action, _point: Point| {
+            label.text(format!("Chose {}", chosen));
+        });
+        menu.add(&action);
+    };
+
+    let file = Menu::new("&File");
+    action(&file, "&New", "Ctrl+N");
+    action(&file, "&Open", "Ctrl+O");
+    let recent = Menu::new("&Recent");
+    action(&recent, "notes.txt", "");
+    action(&recent, "todo.txt", "");
+    file.add(&recent);
+    file.add(&Separator::new());
+    action(&file, "&Quit", "Ctrl+Q");
+
+    let edit = Menu::new("&Edit");
+    action(&edit, "Cu&t", "Ctrl+X");
+    action(&edit, "&Copy", "Ctrl+C");
+    action(&edit, "&Paste", "Ctrl+V");
+
+    let view = Menu::new("&View");
+    let grid = Action::new("Show &Grid");
+    grid.checkable(true).checked(true);
+    grid.on_click(|action: &Action, _point: Point| println!("grid {}", action.is_checked()));
+    view.add(&grid);
+    view.add(&Separator::new());
+    for &zoom in ["50%", "100%", "200%"].iter() {
+        let action = Action::new(zoom);
+        action.radio("zoom").checked(zoom == "100%");
+        action.on_click(move |_action: &Action, _point: Point| println!("zoom {}", zoom));
+        view.add(&action);
+    }
+    view.add(&Separator::new());
+    let full_screen = Action::new("&Full Screen");
+    full_screen.enabled(false);
+    view.add(&full_screen);
+
+    let help = Menu::new("&Help");
+    action(&help, "&About", "F1");
+    let website = Action::new("&Website");
+    if let Err(err) = website.icon_from_path("res/icon_small.png") {
+        println!("{}", err);
+    }
+    help.add(&website);
+
+    let menu_bar = MenuBar::new();
+    menu_bar.position(0, 0).size(420, 24);
+    menu_bar.add(&file).add(&edit).add(&view).add(&help);
+    window.add(&menu_bar);
+
+    for (accelerator, actions) in window.accelerator_conflicts() {
+        println!("{} is bound to {}", accelerator, actions.join(", "));
+    }
+
+    window.exec();
+}
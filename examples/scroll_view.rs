@@ -0,0 +1,31 @@
+extern crate orbtk;
+
+use orbtk::{Color, Window, Image, Rect, Point, Renderer, ScrollView};
+use orbtk::traits::{Click, Place};
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+fn main() {
+    let mut window = Window::new(Rect::new(100, 100, 420, 420), "Scroll View");
+
+    let click_pos: Rc<RefCell<Option<Point>>>= Rc::new(RefCell::new(None));
+
+    // A canvas larger than the window, drawn by clicking
+    let canvas = Image::from_color(1200, 900, Color::rgb(255, 255, 255));
+    canvas.on_click(move |canvas: &Image, point: Point| {
+        let mut prev_opt = click_pos.borrow_mut();
+        if let Some(prev_position) = *prev_opt {
+            let mut image = canvas.image.borrow_mut();
+            image.line(prev_position.x, prev_position.y, point.x, point.y, Color::rgb(0, 0, 0));
+        }
+        *prev_opt = Some(point);
+    });
+
+    let scroll_view = ScrollView::new();
+    scroll_view.position(10, 10).size(400, 400);
+    scroll_view.content(&canvas);
+    window.add(&scroll_view);
+
+    window.exec();
+}
@@ -0,0 +1,56 @@
+extern crate orbtk;
+
+use orbtk::{Window, Rect, Table, CellEditor, SelectionMode};
+use orbtk::traits::Place;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn main() {
+    let mut window = Window::new(Rect::new(100, 100, 580, 420), "Table");
+
+    // Edited cells, over the generated ones
+    let edits: Rc<RefCell<HashMap<(usize, usize), String>>> = Rc::new(RefCell::new(HashMap::new()));
+
+    // Cells are only requested for visible rows
+    let table = Table::new();
+    table.position(10, 10).size(560, 400);
+    table.column("Name", 200)
+        .column("Size", 100)
+        .column("Kind", 180)
+        .column("Done", 60);
+    table.column_editor(0, CellEditor::Text)
+        .column_editor(1, CellEditor::Text)
+        .column_editor(2, CellEditor::Choice(vec!["Text".to_string(), "Document".to_string(), "Image".to_string()]))
+        .column_editor(3, CellEditor::Check);
+    table.selection_mode(SelectionMode::Extended);
+
+    let model_edits = edits.clone();
+    table.model(100000, move |_, row, column| {
+        if let Some(text) = model_edits.borrow().get(&(row, column)) {
+            return text.clone();
+        }
+        match column {
+            0 => format!("file_{}.txt", row),
+            1 => format!("{}", (row * 7919) % 100000),
+            2 => if row % 3 == 0 { "Text".to_string() } else { "Document".to_string() },
+            _ => "false".to_string(),
+        }
+    });
+
+    // Sizes must be numbers
+    table.on_cell_edit(move |table, row, column, text| {
+        if column == 1 && text.parse::<u64>().is_err() {
+            return false;
+        }
+        edits.borrow_mut().insert((row, column), text.to_string());
+        table.refresh();
+        true
+    });
+    table.on_sort(|_, column, order| println!("sorted by {} {:?}", column, order));
+    table.on_selection_changed(|table| println!("selected {:?}", table.selected_rows()));
+    window.add(&table);
+
+    window.exec();
+}
@@ -0,0 +1,45 @@
+extern crate orbtk;
+
+use orbtk::{Window, Rect, TreeView, TreeNode};
+use orbtk::traits::{Click, Place};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn main() {
+    let mut window = Window::new(Rect::new(100, 100, 420, 420), "Tree View");
+
+    // Folders are only read when they are expanded
+    let tree = TreeView::new();
+    tree.position(10, 10).size(400, 400);
+
+    let root = TreeNode::new("/");
+    root.expandable(true);
+    tree.add(&root);
+
+    tree.on_expand(|_, node| {
+        if node.has_children() {
+            return;
+        }
+
+        let path = PathBuf::from(node.text.get());
+        if let Ok(read_dir) = fs::read_dir(&path) {
+            let mut paths: Vec<PathBuf> = read_dir.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+            paths.sort();
+            for path in paths {
+                let child = TreeNode::new(path.to_string_lossy().into_owned());
+                child.expandable(path.is_dir());
+                child.on_click(|node, _| println!("open {}", node.text.get()));
+                node.add(&child);
+            }
+        }
+    });
+    tree.on_selection_changed(|tree| {
+        if let Some(node) = tree.selected() {
+            println!("selected {}", node.text.get());
+        }
+    });
+    window.add(&tree);
+
+    window.exec();
+}
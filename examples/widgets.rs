@@ -1,61 +1,53 @@
 extern crate orbtk;
 
-use orbtk::{Action, Button, Grid, Image, Label, Menu, Point, ProgressBar, Rect, Separator, TextBox, Window, ControlKnob, Toolbar, ToolbarIcon};
-use orbtk::traits::{Border, Click, Enter, Place, Text};
+use orbtk::{Action, Button, DeadKeys, Grid, Image, Label, Menu, Point, ProgressBar, Rect, Separator, TextBox, Window, ControlKnob, Toolbar, ToolbarIcon, ToolbarMode};
+use orbtk::traits::{Border, Click, Enter, Place, Text, Tooltip};
+
+use std::sync::Arc;
 
 fn main() {
     let mut window = Window::new(Rect::new(100, 100, 420, 730), "OrbTK");
+    window.input_method(DeadKeys::new());
     
-    let parent_window = &mut window as *mut Window;  //pointer to the parent window to be used with toolbar widget
-    
-    //populate toolbar with  icon and action
-    let mut toolbar = Toolbar::new();  // create new empty toolbar
-    
-    let mut x = 10;
-    let mut y = 20;
-    
-    //populate toolbar with first icon and action
-    match ToolbarIcon::from_path("res/toolbar_icon.png") {
-        Ok(item) => {
-            let toolbar_clone = &mut toolbar as *mut Toolbar;
-            item.position(x, y)
-                 .text("Tooltip text here".to_owned())
-                 .on_click(move |_image: &ToolbarIcon, _point: Point| {
-                               unsafe{(&mut *toolbar_clone).toggle();} //toggle item 
-                               println!("You have clicked on Toolbar icon 1!"); 
-                               });
-
-            toolbar.add(&item,parent_window);  //add item to toolbar and show icon on window
-            
-            x += item.rect.get().width as i32 + 2; // uncomment for next toolbar icon
-        }
-        Err(err) => {
-            println!("Error loading toolbar element {}",err);
-        }
-    }
-    
-    //populate toolbar with second icon and action
-    match ToolbarIcon::from_path("res/toolbar_icon.png") {
-        Ok(item) => {
-            let toolbar_clone = &mut toolbar as *mut Toolbar;
-            item.position(x, y)
-                 .text("Tooltip text here".to_owned())
-                 .on_click(move |_image: &ToolbarIcon, _point: Point| {
-                                unsafe{(&mut *toolbar_clone).toggle();} //toggle item
-                               println!("You have clicked on Toolbar icon 2!"); 
-                               });
-
-            toolbar.add(&item,parent_window);  //add item to toolbar and show icon on window
-            
-            //x += item.rect.get().width as i32 + 2; // uncomment for next toolbar icon
-        }
-        Err(err) => {
-            println!("Error loading toolbar element {}",err);
+    //populate toolbar with icons and actions
+    let toolbar = Toolbar::new();  // create new empty toolbar
+    // too narrow for the last icon, which goes to the overflow menu
+    toolbar.mode(ToolbarMode::TextBesideIcon)
+        .position(10, 20)
+        .size(260, 30);
+
+    // two exclusive tools, a toggle button and a momentary button
+    for &(name, kind) in [("Pencil", "tool"), ("Brush", "tool"), ("Grid", "toggle"), ("Clear", "")].iter() {
+        match ToolbarIcon::from_path("res/toolbar_icon.png") {
+            Ok(item) => {
+                item.tooltip(name)
+                     .on_click(move |_image: &ToolbarIcon, _point: Point| {
+                                   println!("You have clicked on Toolbar icon {}!", name);
+                                   });
+                match kind {
+                    "tool" => { item.radio("tool"); },
+                    "toggle" => { item.checkable(true); },
+                    _ => (),
+                }
+
+                if name == "Grid" {
+                    toolbar.add_separator();
+                }
+                toolbar.add(&item);  //add item to toolbar, which lays it out
+            }
+            Err(err) => {
+                println!("Error loading toolbar element {}",err);
+            }
         }
     }
-
-    x = 10;
-    y = 0;
+    toolbar.on_selection_changed(|toolbar: &Toolbar, item: &Arc<ToolbarIcon>| {
+        let active = toolbar.active("tool").map(|tool| tool.tooltip_text.get());
+        println!("{} selected: {}, active tool: {:?}", item.tooltip_text.get(), item.selected.get(), active);
+    });
+    window.add(&toolbar);
+
+    let x = 10;
+    let mut y = 0;
     
 
     let menu = Menu::new("Menu");
@@ -86,6 +78,7 @@ fn main() {
         .size(48 + 12, text_box.rect.get().height)
         .text("Update")
         .text_offset(6, 6)
+        .tooltip("Show the text\nin the label above")
         .on_click(move |_button: &Button, _point: Point| {
             text_box.emit_enter();
         });
@@ -125,7 +118,9 @@ fn main() {
     let multi_line_text_box = TextBox::new();
     multi_line_text_box.position(x, y)
         .size(400, 130)
-        .text_offset(1, 1);
+        .text_offset(1, 1)
+        .multi_line(true)
+        .line_numbers(true);
     window.add(&multi_line_text_box);
 
     y += multi_line_text_box.rect.get().height as i32 + 10;
@@ -199,9 +194,9 @@ fn main() {
         .text_offset(6, 6)
         .on_click(move |_button: &Button, _point: Point| {
             //remove widget by id
-            unsafe{(&mut *window_clone).remove(2);}
+            unsafe{(&mut *window_clone).remove(1);}
             //unhide widget by id
-            unsafe{(&mut *window_clone).unhide(13);}
+            unsafe{(&mut *window_clone).unhide(12);}
         });
     window.add(&hideid_button);
 
@@ -223,6 +218,19 @@ fn main() {
         menu.add(&action);
     }
 
+    {
+        let submenu = Menu::new("Offset");
+        for &(name, text) in [("Left", "Left Offset"), ("Right", "Right Offset")].iter() {
+            let action = Action::new(name);
+            let offset_label_clone = offset_label.clone();
+            action.on_click(move |_action: &Action, _point: Point| {
+                offset_label_clone.text.set(text.to_owned());
+            });
+            submenu.add(&action);
+        }
+        menu.add(&submenu);
+    }
+
     menu.add(&Separator::new());
 
     {
@@ -268,7 +276,7 @@ fn main() {
 
     window.add(&grid);
 
-    // Add this last to put it on top
+    // The open menu is drawn above the other widgets wherever it is added
     window.add(&menu);
 
     window.exec();
@@ -0,0 +1,140 @@
+use std::fmt;
+
+use event::Event;
+
+/// A key used in an accelerator
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A character key, stored in lower case
+    Char(char),
+    /// A function key, from F1 to F12
+    Function(u8),
+}
+
+/// A key combination running an action, such as Ctrl+S or F5
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Accelerator {
+    pub fn new(key: Key, ctrl: bool, shift: bool, alt: bool) -> Self {
+        let key = match key {
+            Key::Char(c) => Key::Char(c.to_lowercase().next().unwrap_or(c)),
+            key => key,
+        };
+        Accelerator {
+            key: key,
+            ctrl: ctrl,
+            shift: shift,
+            alt: alt,
+        }
+    }
+
+    /// Parse an accelerator written like "Ctrl+Shift+N" or "F5"
+    /// A character key needs Ctrl or Alt, as it would type text otherwise.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (mut ctrl, mut shift, mut alt) = (false, false, false);
+        let mut key = None;
+        for part in text.split('+') {
+            if key.is_some() {
+                return None;
+            }
+            match part.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "shift" => shift = true,
+                "alt" => alt = true,
+                name => {
+                    let mut chars = name.chars();
+                    key = match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(Key::Char(c)),
+                        (Some('f'), Some(_)) => match name[1..].parse::<u8>() {
+                            Ok(n) if n >= 1 && n <= 12 => Some(Key::Function(n)),
+                            _ => return None,
+                        },
+                        _ => return None,
+                    };
+                },
+            }
+        }
+
+        match key {
+            Some(Key::Char(_)) if !ctrl && !alt => None,
+            Some(key) => Some(Accelerator::new(key, ctrl, shift, alt)),
+            None => None,
+        }
+    }
+
+    /// The accelerator typed by a key event, if the event can be one
+    pub fn from_event(event: &Event) -> Option<Self> {
+        match *event {
+            Event::Shortcut { c, ctrl, shift, alt } => Some(Accelerator::new(Key::Char(c), ctrl, shift, alt)),
+            Event::Function { n, ctrl, shift, alt } => Some(Accelerator::new(Key::Function(n), ctrl, shift, alt)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        match self.key {
+            Key::Char(c) => write!(f, "{}", c.to_uppercase().collect::<String>()),
+            Key::Function(n) => write!(f, "F{}", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Accelerator, Key};
+
+    #[test]
+    fn parse_modifiers() {
+        assert_eq!(Accelerator::parse("Ctrl+S"), Some(Accelerator::new(Key::Char('s'), true, false, false)));
+        assert_eq!(Accelerator::parse("ctrl+shift+n"), Some(Accelerator::new(Key::Char('n'), true, true, false)));
+        assert_eq!(Accelerator::parse("Alt + X"), Some(Accelerator::new(Key::Char('x'), false, false, true)));
+        assert_eq!(Accelerator::parse("Shift+Control+Alt+1"), Some(Accelerator::new(Key::Char('1'), true, true, true)));
+    }
+
+    #[test]
+    fn parse_function_keys() {
+        assert_eq!(Accelerator::parse("F5"), Some(Accelerator::new(Key::Function(5), false, false, false)));
+        assert_eq!(Accelerator::parse("Shift+F12"), Some(Accelerator::new(Key::Function(12), false, true, false)));
+        assert_eq!(Accelerator::parse("f1"), Some(Accelerator::new(Key::Function(1), false, false, false)));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(Accelerator::parse(""), None);
+        assert_eq!(Accelerator::parse("Ctrl"), None);
+        assert_eq!(Accelerator::parse("Ctrl+"), None);
+        assert_eq!(Accelerator::parse("S"), None);
+        assert_eq!(Accelerator::parse("Shift+S"), None);
+        assert_eq!(Accelerator::parse("Ctrl+S+T"), None);
+        assert_eq!(Accelerator::parse("Ctrl+Tab"), None);
+        assert_eq!(Accelerator::parse("F0"), None);
+        assert_eq!(Accelerator::parse("F13"), None);
+        assert_eq!(Accelerator::parse("Fx"), None);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["Ctrl+S", "Ctrl+Shift+N", "Alt+F4", "F5"].iter() {
+            let accelerator = Accelerator::parse(text).unwrap();
+            assert_eq!(accelerator.to_string(), *text);
+            assert_eq!(Accelerator::parse(&accelerator.to_string()), Some(accelerator));
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use { Color, InnerWindow, Window, List, Entry, Label, Point, Button };
-use traits::{ Place, Text, Click };
+use { Color, InnerWindow, Window, List, Entry, Label, Point, Button, TextBox };
+use traits::{ Place, Text, Click, Enter };
 
 use std::{fs, io};
 use std::cell::RefCell;
@@ -122,6 +122,7 @@ impl FileDialog {
                             }
 
                             let entry = Entry::new(24);
+                            entry.text(name.clone());
 
                             let label = Label::new();
                             label.position(2, 2).size(w - 8, 20).text_offset(2, 2);
@@ -165,6 +166,22 @@ impl FileDialog {
             }
 
             window.add(&list);
+
+            // Typing a name jumps to it in the list, and the filter hides other names
+            let filter = TextBox::new();
+            filter.position(2, (h - 30) as i32).size(w / 2 - 8, 24).text_offset(4, 4);
+            {
+                let list = list.clone();
+                filter.on_enter(move |filter: &TextBox| {
+                    let pattern = filter.text.get().to_lowercase();
+                    if pattern.is_empty() {
+                        list.clear_filter();
+                    } else {
+                        list.filter(move |entry| entry.text.borrow().to_lowercase().contains(&pattern) || &*entry.text.borrow() == "../");
+                    }
+                });
+            }
+            window.add(&filter);
             
                 //Cancell button
             let cancel_button = Button::new();
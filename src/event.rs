@@ -19,7 +19,39 @@ pub enum Event {
     Text {
         c: char,
     },
+
+    /// Append a character to the text being composed by an input method
+    Preedit {
+        c: char,
+    },
+    /// Discard the text being composed
+    PreeditClear,
+    /// Insert the text being composed, ending the composition
+    Commit,
+
+    /// The Ctrl, Shift or Alt key was pressed or released
+    Modifiers {
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+    },
+    /// A character key pressed while Ctrl or Alt is held, which does not insert text
+    Shortcut {
+        c: char,
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+    },
+    /// A function key, from F1 to F12
+    Function {
+        n: u8,
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+    },
+
     Enter,
+    Escape,
 
     Backspace,
     Delete,
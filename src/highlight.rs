@@ -0,0 +1,418 @@
+//! Tokenizers used for syntax highlighting
+
+use std::cmp;
+
+/// The kind of a token, used to pick its color
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum TokenKind {
+    Text,
+    Keyword,
+    Type,
+    Literal,
+    Number,
+    String,
+    Comment,
+    Key,
+    Attribute,
+    Macro,
+    Punctuation,
+}
+
+/// A token, as a byte range of the tokenized text
+#[derive(Copy, Clone, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, start: usize, end: usize) -> Self {
+        Token {
+            kind: kind,
+            start: start,
+            end: end,
+        }
+    }
+}
+
+pub trait Tokenizer {
+    /// Split text into tokens, in order
+    /// Text between tokens is drawn with the default color.
+    fn tokenize(&self, text: &str) -> Vec<Token>;
+}
+
+/// A cursor over text, shared by the tokenizers
+struct Scanner<'a> {
+    text: &'a str,
+    i: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Scanner { text: text, i: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.i..].chars().next()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.text[self.i..].chars().nth(n)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.text[self.i..].starts_with(s)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if let Some(c) = c {
+            self.i += c.len_utf8();
+        }
+        c
+    }
+
+    fn skip(&mut self, s: &str) {
+        self.i += s.len();
+    }
+
+    fn bump_while<F: Fn(char) -> bool>(&mut self, f: F) {
+        while let Some(c) = self.peek() {
+            if !f(c) {
+                break;
+            }
+            self.i += c.len_utf8();
+        }
+    }
+
+    /// Skip to the end of the line, not including the newline
+    fn bump_line(&mut self) {
+        self.bump_while(|c| c != '\n');
+    }
+
+    /// Skip past the next occurrence of end, or to the end of the text
+    fn bump_until(&mut self, end: &str) {
+        match self.text[self.i..].find(end) {
+            Some(i) => self.i += i + end.len(),
+            None => self.i = self.text.len(),
+        }
+    }
+
+    /// Skip a quoted string, handling backslash escapes
+    /// Strings are not allowed to span lines unless multi_line is set.
+    fn bump_string(&mut self, quote: char, escapes: bool, multi_line: bool) {
+        self.bump();
+        while let Some(c) = self.peek() {
+            if c == '\n' && !multi_line {
+                break;
+            }
+            self.bump();
+            if c == '\\' && escapes {
+                self.bump();
+            } else if c == quote {
+                break;
+            }
+        }
+    }
+
+    /// Skip a raw string such as r#"..."#, returning false if there is none after the prefix
+    fn bump_raw_string(&mut self, prefix: &str) -> bool {
+        if !self.starts_with(prefix) {
+            return false;
+        }
+        let text = self.text;
+        let rest = &text[self.i + prefix.len()..];
+        let hashes = rest.chars().take_while(|&c| c == '#').count();
+        if !rest[hashes..].starts_with('"') {
+            return false;
+        }
+        self.i += prefix.len() + hashes + 1;
+        self.bump_until(&format!("\"{}", &rest[..hashes]));
+        true
+    }
+
+    /// Skip a number, including a fraction but not a range or method call following it
+    fn bump_number(&mut self) {
+        loop {
+            self.bump_while(|c| c.is_alphanumeric() || c == '_');
+            if self.peek() == Some('.') && self.peek_at(1).map_or(false, |c| c.is_digit(10)) {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn word(&self, start: usize) -> &'a str {
+        &self.text[start..self.i]
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_punctuation(c: char) -> bool {
+    "{}[]()<>.,;:=+-*/%!&|^~?@#$".contains(c)
+}
+
+const RUST_KEYWORDS: &'static [&'static str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+    "unsafe", "use", "where", "while",
+];
+
+const RUST_TYPES: &'static [&'static str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize",
+    "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64",
+];
+
+/// Rust source code
+pub struct RustTokenizer;
+
+impl Tokenizer for RustTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut s = Scanner::new(text);
+
+        while let Some(c) = s.peek() {
+            let start = s.i;
+            let kind = if s.starts_with("//") {
+                s.bump_line();
+                TokenKind::Comment
+            } else if s.starts_with("/*") {
+                s.skip("/*");
+                s.bump_until("*/");
+                TokenKind::Comment
+            } else if s.starts_with("#[") || s.starts_with("#![") {
+                s.bump_until("]");
+                TokenKind::Attribute
+            } else if c == '"' {
+                s.bump_string('"', true, true);
+                TokenKind::String
+            } else if s.bump_raw_string("r") || s.bump_raw_string("br") {
+                TokenKind::String
+            } else if c == 'b' && s.peek_at(1) == Some('"') {
+                s.bump();
+                s.bump_string('"', true, true);
+                TokenKind::String
+            } else if c == '\'' {
+                // Either a character literal or a lifetime
+                if s.peek_at(1) == Some('\\') || s.peek_at(2) == Some('\'') {
+                    s.bump_string('\'', true, false);
+                    TokenKind::String
+                } else {
+                    s.bump();
+                    s.bump_while(is_ident);
+                    TokenKind::Text
+                }
+            } else if c.is_digit(10) {
+                s.bump_number();
+                TokenKind::Number
+            } else if is_ident_start(c) {
+                s.bump_while(is_ident);
+                let word = s.word(start);
+                if s.peek() == Some('!') && s.peek_at(1) != Some('=') {
+                    s.bump();
+                    TokenKind::Macro
+                } else if word == "true" || word == "false" {
+                    TokenKind::Literal
+                } else if RUST_KEYWORDS.contains(&word) {
+                    TokenKind::Keyword
+                } else if RUST_TYPES.contains(&word) || word.chars().next().map_or(false, |c| c.is_uppercase()) {
+                    TokenKind::Type
+                } else {
+                    TokenKind::Text
+                }
+            } else if is_punctuation(c) {
+                s.bump();
+                TokenKind::Punctuation
+            } else {
+                s.bump();
+                continue;
+            };
+
+            tokens.push(Token::new(kind, start, s.i));
+        }
+
+        tokens
+    }
+}
+
+/// TOML configuration files
+pub struct TomlTokenizer;
+
+impl Tokenizer for TomlTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut s = Scanner::new(text);
+        // Keys are only found at the start of a line, before the '=', and not inside a value
+        let mut line_start = true;
+        // Depth of the arrays of the current value, which may span lines
+        let mut depth = 0;
+
+        while let Some(c) = s.peek() {
+            let start = s.i;
+            let kind = if c == '\n' {
+                s.bump();
+                line_start = depth == 0;
+                continue;
+            } else if c.is_whitespace() {
+                s.bump();
+                continue;
+            } else if c == '#' {
+                s.bump_line();
+                TokenKind::Comment
+            } else if line_start && c == '[' {
+                s.bump_while(|c| c != ']' && c != '\n');
+                s.bump_while(|c| c == ']');
+                TokenKind::Key
+            } else if s.starts_with("\"\"\"") || s.starts_with("'''") {
+                let quotes = &text[start..start + 3];
+                s.skip(quotes);
+                s.bump_until(quotes);
+                TokenKind::String
+            } else if c == '"' || c == '\'' {
+                s.bump_string(c, c == '"', false);
+                if line_start { TokenKind::Key } else { TokenKind::String }
+            } else if line_start && (is_ident(c) || c == '-') {
+                s.bump_while(|c| is_ident(c) || c == '-' || c == '.');
+                TokenKind::Key
+            } else if c.is_digit(10) || ((c == '+' || c == '-') && s.peek_at(1).map_or(false, |c| c.is_digit(10))) {
+                // Also covers dates and times, which are colored like numbers
+                s.bump();
+                s.bump_while(|c| c.is_alphanumeric() || "_.:-+".contains(c));
+                TokenKind::Number
+            } else if is_ident_start(c) {
+                s.bump_while(is_ident);
+                match s.word(start) {
+                    "true" | "false" | "inf" | "nan" => TokenKind::Literal,
+                    _ => TokenKind::Text,
+                }
+            } else {
+                s.bump();
+                match c {
+                    '=' => line_start = false,
+                    '[' => depth += 1,
+                    ']' => depth = cmp::max(depth, 1) - 1,
+                    _ => (),
+                }
+                TokenKind::Punctuation
+            };
+
+            tokens.push(Token::new(kind, start, s.i));
+        }
+
+        tokens
+    }
+}
+
+/// JSON documents
+pub struct JsonTokenizer;
+
+impl Tokenizer for JsonTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut s = Scanner::new(text);
+
+        while let Some(c) = s.peek() {
+            let start = s.i;
+            let kind = if c == '"' {
+                s.bump_string('"', true, false);
+                // A string followed by a colon is an object key
+                if text[s.i..].chars().skip_while(|c| c.is_whitespace()).next() == Some(':') {
+                    TokenKind::Key
+                } else {
+                    TokenKind::String
+                }
+            } else if c.is_digit(10) || c == '-' {
+                s.bump();
+                s.bump_while(|c| c.is_digit(10) || "eE.+-".contains(c));
+                TokenKind::Number
+            } else if is_ident_start(c) {
+                s.bump_while(is_ident);
+                match s.word(start) {
+                    "true" | "false" | "null" => TokenKind::Literal,
+                    _ => TokenKind::Text,
+                }
+            } else if is_punctuation(c) {
+                s.bump();
+                TokenKind::Punctuation
+            } else {
+                s.bump();
+                continue;
+            };
+
+            tokens.push(Token::new(kind, start, s.i));
+        }
+
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonTokenizer, RustTokenizer, TokenKind, Tokenizer, TomlTokenizer};
+
+    fn tokens<'a, T: Tokenizer>(tokenizer: T, text: &'a str) -> Vec<(TokenKind, &'a str)> {
+        tokenizer.tokenize(text).iter().map(|token| (token.kind, &text[token.start..token.end])).collect()
+    }
+
+    #[test]
+    fn rust_raw_strings() {
+        assert_eq!(tokens(RustTokenizer, r####"r"a\" x"####),
+                   vec![(TokenKind::String, r#"r"a\""#), (TokenKind::Text, "x")]);
+        assert_eq!(tokens(RustTokenizer, r####"r##"a "# b"## x"####),
+                   vec![(TokenKind::String, r###"r##"a "# b"##"###), (TokenKind::Text, "x")]);
+        assert_eq!(tokens(RustTokenizer, r####"br#"a"# x"####),
+                   vec![(TokenKind::String, r##"br#"a"#"##), (TokenKind::Text, "x")]);
+        // Raw identifiers are not strings
+        assert_eq!(tokens(RustTokenizer, "r#type"),
+                   vec![(TokenKind::Text, "r"), (TokenKind::Punctuation, "#"), (TokenKind::Keyword, "type")]);
+    }
+
+    #[test]
+    fn rust_numbers() {
+        assert_eq!(tokens(RustTokenizer, "1..10"),
+                   vec![(TokenKind::Number, "1"), (TokenKind::Punctuation, "."),
+                        (TokenKind::Punctuation, "."), (TokenKind::Number, "10")]);
+        assert_eq!(tokens(RustTokenizer, "1.5e3 0x_ff"),
+                   vec![(TokenKind::Number, "1.5e3"), (TokenKind::Number, "0x_ff")]);
+        assert_eq!(tokens(RustTokenizer, "2.pow(3)"),
+                   vec![(TokenKind::Number, "2"), (TokenKind::Punctuation, "."), (TokenKind::Text, "pow"),
+                        (TokenKind::Punctuation, "("), (TokenKind::Number, "3"), (TokenKind::Punctuation, ")")]);
+    }
+
+    #[test]
+    fn toml_multi_line_arrays() {
+        let text = "a = [\n  1,\n  \"x\",\n  [true],\n]\nb = 2\n";
+        assert_eq!(tokens(TomlTokenizer, text),
+                   vec![(TokenKind::Key, "a"), (TokenKind::Punctuation, "="), (TokenKind::Punctuation, "["),
+                        (TokenKind::Number, "1"), (TokenKind::Punctuation, ","),
+                        (TokenKind::String, "\"x\""), (TokenKind::Punctuation, ","),
+                        (TokenKind::Punctuation, "["), (TokenKind::Literal, "true"), (TokenKind::Punctuation, "]"),
+                        (TokenKind::Punctuation, ","), (TokenKind::Punctuation, "]"),
+                        (TokenKind::Key, "b"), (TokenKind::Punctuation, "="), (TokenKind::Number, "2")]);
+    }
+
+    #[test]
+    fn toml_tables() {
+        assert_eq!(tokens(TomlTokenizer, "[package]\n\"a b\" = 'c'"),
+                   vec![(TokenKind::Key, "[package]"), (TokenKind::Key, "\"a b\""),
+                        (TokenKind::Punctuation, "="), (TokenKind::String, "'c'")]);
+    }
+
+    #[test]
+    fn json_keys() {
+        assert_eq!(tokens(JsonTokenizer, r#"{"a": [-1.5, null]}"#),
+                   vec![(TokenKind::Punctuation, "{"), (TokenKind::Key, "\"a\""), (TokenKind::Punctuation, ":"),
+                        (TokenKind::Punctuation, "["), (TokenKind::Number, "-1.5"), (TokenKind::Punctuation, ","),
+                        (TokenKind::Literal, "null"), (TokenKind::Punctuation, "]"), (TokenKind::Punctuation, "}")]);
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+
+use event::Event;
+
+/// Turns typed characters into text events, possibly composing several
+/// keys into one character through `Event::Preedit` and `Event::Commit`
+pub trait InputMethod {
+    /// Handle a typed character, pushing the resulting events
+    /// Returns false to have the character sent as a plain `Event::Text`.
+    fn key(&mut self, c: char, events: &mut VecDeque<Event>) -> bool;
+
+    /// Abandon any composition, when a key that is not text is pressed
+    fn reset(&mut self, events: &mut VecDeque<Event>);
+}
+
+/// Composes accented letters from a dead key followed by a letter,
+/// for example '^' then 'e' gives 'ê'
+pub struct DeadKeys {
+    pending: Option<char>,
+}
+
+impl DeadKeys {
+    pub fn new() -> Self {
+        DeadKeys { pending: None }
+    }
+
+    fn is_dead(c: char) -> bool {
+        match c {
+            '`' | '´' | '^' | '¨' | '~' => true,
+            _ => false,
+        }
+    }
+
+    fn compose(dead: char, c: char) -> Option<char> {
+        let (from, to) = match dead {
+            '`' => ("aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+            '´' => ("aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+            '^' => ("aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+            '¨' => ("aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+            '~' => ("anoANO", "ãñõÃÑÕ"),
+            _ => return None,
+        };
+        from.chars().position(|f| f == c).and_then(|i| to.chars().nth(i))
+    }
+}
+
+impl InputMethod for DeadKeys {
+    fn key(&mut self, c: char, events: &mut VecDeque<Event>) -> bool {
+        match self.pending.take() {
+            Some(dead) => {
+                events.push_back(Event::PreeditClear);
+                if let Some(composed) = DeadKeys::compose(dead, c) {
+                    events.push_back(Event::Preedit { c: composed });
+                    events.push_back(Event::Commit);
+                    true
+                } else {
+                    // Not composable, so the dead key is typed as itself
+                    events.push_back(Event::Preedit { c: dead });
+                    events.push_back(Event::Commit);
+                    if c == dead || c == ' ' {
+                        true
+                    } else {
+                        self.key(c, events)
+                    }
+                }
+            },
+            None => if DeadKeys::is_dead(c) {
+                self.pending = Some(c);
+                events.push_back(Event::Preedit { c: c });
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    fn reset(&mut self, events: &mut VecDeque<Event>) {
+        if self.pending.take().is_some() {
+            events.push_back(Event::PreeditClear);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use event::Event;
+    use super::{DeadKeys, InputMethod};
+
+    /// Type the characters, returning the events and the text they produce
+    fn type_keys(input_method: &mut DeadKeys, keys: &str) -> (Vec<String>, String) {
+        let mut events = VecDeque::new();
+        for c in keys.chars() {
+            if !input_method.key(c, &mut events) {
+                events.push_back(Event::Text { c: c });
+            }
+        }
+
+        let (mut text, mut preedit) = (String::new(), String::new());
+        for event in events.iter() {
+            match *event {
+                Event::Text { c } => text.push(c),
+                Event::Preedit { c } => preedit.push(c),
+                Event::PreeditClear => preedit.clear(),
+                Event::Commit => text.push_str(&preedit.split_off(0)),
+                _ => (),
+            }
+        }
+        (events.iter().map(|event| format!("{:?}", event)).collect(), text)
+    }
+
+    #[test]
+    fn plain_keys_are_not_handled() {
+        let (events, text) = type_keys(&mut DeadKeys::new(), "ab");
+        assert_eq!(events, vec!["Text { c: 'a' }", "Text { c: 'b' }"]);
+        assert_eq!(text, "ab");
+    }
+
+    #[test]
+    fn dead_key_composes_with_letter() {
+        let (events, text) = type_keys(&mut DeadKeys::new(), "^e");
+        assert_eq!(events, vec!["Preedit { c: '^' }", "PreeditClear", "Preedit { c: 'ê' }", "Commit"]);
+        assert_eq!(text, "ê");
+
+        assert_eq!(type_keys(&mut DeadKeys::new(), "´y~N¨o`U").1, "ýÑöÙ");
+    }
+
+    #[test]
+    fn unknown_pair_types_both_keys() {
+        let (events, text) = type_keys(&mut DeadKeys::new(), "^x");
+        assert_eq!(events, vec!["Preedit { c: '^' }", "PreeditClear", "Preedit { c: '^' }", "Commit", "Text { c: 'x' }"]);
+        assert_eq!(text, "^x");
+
+        // A dead key following another composes in turn
+        assert_eq!(type_keys(&mut DeadKeys::new(), "~`a").1, "~à");
+    }
+
+    #[test]
+    fn dead_key_repeated_or_with_space_types_itself() {
+        assert_eq!(type_keys(&mut DeadKeys::new(), "^^").1, "^");
+        assert_eq!(type_keys(&mut DeadKeys::new(), "¨ ").1, "¨");
+    }
+
+    #[test]
+    fn reset_after_dead_key_clears_preedit() {
+        let mut input_method = DeadKeys::new();
+        type_keys(&mut input_method, "^");
+
+        let mut events = VecDeque::new();
+        input_method.reset(&mut events);
+        assert_eq!(events.iter().map(|event| format!("{:?}", event)).collect::<Vec<_>>(), vec!["PreeditClear"]);
+
+        // Nothing is pending any more
+        events.clear();
+        input_method.reset(&mut events);
+        assert!(events.is_empty());
+        assert_eq!(type_keys(&mut input_method, "e").1, "e");
+    }
+}
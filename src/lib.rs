@@ -5,25 +5,33 @@
 
 extern crate orbclient;
 extern crate orbimage;
+extern crate unicode_bidi;
+extern crate unicode_segmentation;
 
 pub use orbclient::color::Color;
 pub use orbclient::renderer::Renderer;
 
+pub use accelerator::{Accelerator, Key};
 pub use cell::CloneCell;
 pub use dialogs::*;
 pub use event::Event;
+pub use input_method::{InputMethod, DeadKeys};
 pub use point::Point;
 pub use rect::Rect;
 pub use traits::*;
 pub use widgets::*;
 pub use window::{InnerWindow, Window};
 
+pub mod accelerator;
 pub mod cell;
 pub mod dialogs;
 pub mod event;
+pub mod highlight;
+pub mod input_method;
 pub mod point;
 pub mod rect;
 pub mod theme;
+mod text_layout;
 pub mod traits;
 pub mod widgets;
 pub mod window;
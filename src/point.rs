@@ -1,7 +1,7 @@
 use std::ops::{Add, Sub};
 
 /// A point
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
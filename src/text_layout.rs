@@ -0,0 +1,124 @@
+//! Grapheme clusters and bidirectional ordering for the text widgets
+//! Each grapheme cluster takes one character cell, with combining marks
+//! drawn over their base character.
+
+use unicode_bidi::BidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A grapheme cluster placed on a line
+#[derive(Copy, Clone, Debug)]
+pub struct Glyph {
+    /// Byte index of the cluster in the laid out text
+    pub start: usize,
+    pub end: usize,
+    /// Offset from the start of the line
+    pub x: i32,
+    pub width: i32,
+}
+
+/// Find the byte index of the next grapheme cluster
+pub fn next_grapheme(text: &str, i: usize) -> usize {
+    text[i..].graphemes(true).next().map_or(text.len(), |g| i + g.len())
+}
+
+/// Find the byte index of the previous grapheme cluster
+pub fn prev_grapheme(text: &str, i: usize) -> usize {
+    text[..i].graphemes(true).next_back().map_or(0, |g| i - g.len())
+}
+
+/// Width of a grapheme cluster, with tabs spanning tab_width cells
+pub fn grapheme_width(grapheme: &str, tab_width: u32) -> i32 {
+    if grapheme == "\t" {
+        8 * tab_width as i32
+    } else {
+        8
+    }
+}
+
+pub fn text_width(text: &str, tab_width: u32) -> i32 {
+    text.graphemes(true).map(|g| grapheme_width(g, tab_width)).sum()
+}
+
+/// Whether a paragraph reads right to left, from its first strong character
+pub fn is_rtl(paragraph: &str) -> bool {
+    let bidi = BidiInfo::new(paragraph, None);
+    bidi.paragraphs.first().map_or(false, |para| para.level.is_rtl())
+}
+
+/// Place the grapheme clusters of paragraph[start..end] in visual order,
+/// reordering right to left runs
+pub fn layout_line(paragraph: &str, start: usize, end: usize, tab_width: u32) -> Vec<Glyph> {
+    let mut glyphs = Vec::new();
+    let mut x = 0;
+    {
+        let mut place = |offset: usize, graphemes: &mut Iterator<Item = (usize, &str)>| {
+            for (i, g) in graphemes {
+                let width = grapheme_width(g, tab_width);
+                glyphs.push(Glyph {
+                    start: offset + i,
+                    end: offset + i + g.len(),
+                    x: x,
+                    width: width,
+                });
+                x += width;
+            }
+        };
+
+        let bidi = BidiInfo::new(paragraph, None);
+        match bidi.paragraphs.first() {
+            Some(para) if bidi.has_rtl() && start < end => {
+                let (levels, runs) = bidi.visual_runs(para, start..end);
+                for run in runs {
+                    let graphemes = paragraph[run.clone()].grapheme_indices(true);
+                    if levels[run.start].is_rtl() {
+                        place(run.start, &mut graphemes.rev());
+                    } else {
+                        place(run.start, &mut graphemes.into_iter());
+                    }
+                }
+            },
+            _ => place(start, &mut paragraph[start..end].grapheme_indices(true)),
+        }
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_rtl, layout_line, next_grapheme, prev_grapheme, text_width};
+
+    fn placed(paragraph: &str, start: usize, end: usize) -> Vec<(usize, i32)> {
+        layout_line(paragraph, start, end, 4).iter().map(|glyph| (glyph.start, glyph.x)).collect()
+    }
+
+    #[test]
+    fn graphemes_include_combining_marks() {
+        let text = "e\u{301}x";
+        assert_eq!(next_grapheme(text, 0), 3);
+        assert_eq!(prev_grapheme(text, 3), 0);
+        assert_eq!(next_grapheme(text, 4), 4);
+        assert_eq!(prev_grapheme(text, 0), 0);
+        assert_eq!(text_width(text, 4), 16);
+        assert_eq!(text_width("a\tb", 4), 48);
+    }
+
+    #[test]
+    fn direction_follows_the_first_strong_character() {
+        assert!(!is_rtl("abc \u{5d0}"));
+        assert!(is_rtl("123 \u{5d0}\u{5d1} abc"));
+        assert!(!is_rtl(""));
+    }
+
+    #[test]
+    fn left_to_right_runs_keep_their_order() {
+        assert_eq!(placed("ab\tc", 0, 4), vec![(0, 0), (1, 8), (2, 16), (3, 48)]);
+        assert_eq!(placed("abcd", 1, 3), vec![(1, 0), (2, 8)]);
+    }
+
+    #[test]
+    fn right_to_left_runs_are_reversed() {
+        // The Hebrew letters take two bytes each
+        assert_eq!(placed("ab \u{5d0}\u{5d1}", 0, 7), vec![(0, 0), (1, 8), (2, 16), (5, 24), (3, 32)]);
+        assert_eq!(placed("\u{5d0}\u{5d1} ab", 0, 7), vec![(5, 0), (6, 8), (4, 16), (2, 24), (0, 32)]);
+    }
+}
@@ -7,6 +7,7 @@ const fn hex(data: u32) -> Color {
 const BLACK: Color = hex(0x000000);
 const SELECT_BLUE: Color = hex(0x5294E2);
 const BORDER_GREY: Color = hex(0xCFD6E6);
+const TEXT_GREY: Color = hex(0x8B9199);
 const WINDOW_GREY: Color = hex(0xF5F6F7);
 const BUTTON_WHITE: Color = hex(0xFBFBFC);
 const WHITE: Color = hex(0xFFFFFF);
@@ -27,8 +28,35 @@ pub static ITEM_BACKGROUND: Color = WHITE;
 pub static ITEM_BORDER: Color = BORDER_GREY;
 pub static ITEM_FOREGROUND: Color = BLACK;
 pub static ITEM_SELECTION: Color = SELECT_BLUE;
+pub static ITEM_HOVER: Color = hex(0xDCE9F8);
+pub static ITEM_DISABLED: Color = TEXT_GREY;
 
 pub static TEXT_BACKGROUND: Color = WHITE;
 pub static TEXT_BORDER: Color = BORDER_GREY;
 pub static TEXT_FOREGROUND: Color = BLACK;
 pub static TEXT_SELECTION: Color = SELECT_BLUE;
+
+pub static TEXT_MATCH: Color = hex(0xFCE8B2);
+pub static TEXT_MATCH_CURRENT: Color = hex(0xF9B64B);
+
+pub static TEXT_GUTTER_BACKGROUND: Color = WINDOW_GREY;
+pub static TEXT_GUTTER_FOREGROUND: Color = TEXT_GREY;
+
+pub static SCROLL_BAR_BACKGROUND: Color = WINDOW_GREY;
+pub static SCROLL_BAR_FOREGROUND: Color = BORDER_GREY;
+pub static SCROLL_BAR_SELECTION: Color = SELECT_BLUE;
+
+pub static TOOLTIP_BACKGROUND: Color = hex(0xFFFFDC);
+pub static TOOLTIP_BORDER: Color = TEXT_GREY;
+pub static TOOLTIP_FOREGROUND: Color = BLACK;
+
+pub static CODE_KEYWORD: Color = hex(0xA626A4);
+pub static CODE_TYPE: Color = hex(0xC18401);
+pub static CODE_LITERAL: Color = hex(0x0184BC);
+pub static CODE_NUMBER: Color = hex(0x986801);
+pub static CODE_STRING: Color = hex(0x50A14F);
+pub static CODE_COMMENT: Color = hex(0xA0A1A7);
+pub static CODE_KEY: Color = hex(0xE45649);
+pub static CODE_ATTRIBUTE: Color = hex(0x4078F2);
+pub static CODE_MACRO: Color = hex(0x4078F2);
+pub static CODE_BRACKET_MATCH: Color = hex(0xC8DAF2);
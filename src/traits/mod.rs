@@ -5,6 +5,7 @@ pub use self::event_filter::EventFilter;
 pub use self::place::Place;
 pub use self::resize::Resize;
 pub use self::text::Text;
+pub use self::tooltip::Tooltip;
 
 mod border;
 mod click;
@@ -13,3 +14,4 @@ mod event_filter;
 mod place;
 mod resize;
 mod text;
+mod tooltip;
@@ -0,0 +1,12 @@
+use widgets::Widget;
+
+/// Text shown by the window near the mouse, once it rests over the widget
+/// Lines are separated by '\n'. Widgets keep the text in `Widget::tooltip_text`.
+pub trait Tooltip: Sized + Widget {
+    fn tooltip<S: Into<String>>(&self, text: S) -> &Self {
+        if let Some(tooltip_text) = self.tooltip_text() {
+            tooltip_text.set(text.into());
+        }
+        self
+    }
+}
@@ -7,11 +7,12 @@ use event::Event;
 use point::Point;
 use rect::Rect;
 use theme::{BUTTON_BACKGROUND, BUTTON_BG_SELECTION, BUTTON_FOREGROUND, BUTTON_FG_SELECTION, BUTTON_BORDER};
-use traits::{Border, Click, Place, Text};
+use traits::{Border, Click, Place, Text, Tooltip};
 use widgets::Widget;
 
 pub struct Button {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Color,
     pub bg_selected: Color,
     pub fg: Color,
@@ -30,6 +31,7 @@ impl Button {
     pub fn new() -> Arc<Self> {
         Arc::new(Button {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: BUTTON_BACKGROUND,
             bg_selected: BUTTON_BG_SELECTION,
             fg: BUTTON_FOREGROUND,
@@ -85,11 +87,21 @@ impl Text for Button {
     }
 }
 
+impl Tooltip for Button {}
+
 impl Widget for Button {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         if self.visible.get(){
             let rect = self.rect.get();
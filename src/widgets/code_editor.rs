@@ -0,0 +1,365 @@
+use orbclient::{Color, Renderer};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use cell::CloneCell;
+use event::Event;
+use highlight::{Token, TokenKind, Tokenizer};
+use rect::Rect;
+use theme::{CODE_ATTRIBUTE, CODE_BRACKET_MATCH, CODE_COMMENT, CODE_KEY, CODE_KEYWORD, CODE_LITERAL,
+            CODE_MACRO, CODE_NUMBER, CODE_STRING, CODE_TYPE};
+use traits::{Place, Text, Tooltip};
+use widgets::{Span, TextBox, Widget};
+
+fn opening(c: char) -> Option<char> {
+    match c {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+fn closing(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// Tokens of a text and the spans coloring them, kept until the text changes
+struct Highlight {
+    text: String,
+    tokens: Vec<Token>,
+    spans: Vec<Span>,
+}
+
+/// A multi-line text editor for source code and configuration files
+/// Editing is done by the inner `TextBox`, with syntax highlighting,
+/// auto-indent and bracket matching added on top.
+pub struct CodeEditor {
+    pub text_box: Arc<TextBox>,
+    tokenizer: RefCell<Option<Box<Tokenizer>>>,
+    colors: RefCell<BTreeMap<TokenKind, Color>>,
+    highlight: RefCell<Option<Highlight>>,
+    pub auto_indent: Cell<bool>,
+    pub insert_spaces: Cell<bool>,
+    pub bracket_matching: Cell<bool>,
+    pub bg_bracket: Color,
+}
+
+impl CodeEditor {
+    pub fn new() -> Arc<Self> {
+        let text_box = TextBox::new();
+        text_box.multi_line(true)
+            .line_numbers(true)
+            .text_offset(1, 1);
+
+        let mut colors = BTreeMap::new();
+        colors.insert(TokenKind::Keyword, CODE_KEYWORD);
+        colors.insert(TokenKind::Type, CODE_TYPE);
+        colors.insert(TokenKind::Literal, CODE_LITERAL);
+        colors.insert(TokenKind::Number, CODE_NUMBER);
+        colors.insert(TokenKind::String, CODE_STRING);
+        colors.insert(TokenKind::Comment, CODE_COMMENT);
+        colors.insert(TokenKind::Key, CODE_KEY);
+        colors.insert(TokenKind::Attribute, CODE_ATTRIBUTE);
+        colors.insert(TokenKind::Macro, CODE_MACRO);
+
+        Arc::new(CodeEditor {
+            text_box: text_box,
+            tokenizer: RefCell::new(None),
+            colors: RefCell::new(colors),
+            highlight: RefCell::new(None),
+            auto_indent: Cell::new(true),
+            insert_spaces: Cell::new(true),
+            bracket_matching: Cell::new(true),
+            bg_bracket: CODE_BRACKET_MATCH,
+        })
+    }
+
+    pub fn tokenizer<T: Tokenizer + 'static>(&self, tokenizer: T) -> &Self {
+        *self.tokenizer.borrow_mut() = Some(Box::new(tokenizer));
+        *self.highlight.borrow_mut() = None;
+        self
+    }
+
+    /// Override the theme color of a token kind
+    pub fn token_color(&self, kind: TokenKind, color: Color) -> &Self {
+        self.colors.borrow_mut().insert(kind, color);
+        *self.highlight.borrow_mut() = None;
+        self
+    }
+
+    pub fn auto_indent(&self, auto_indent: bool) -> &Self {
+        self.auto_indent.set(auto_indent);
+        self
+    }
+
+    /// Insert spaces instead of a tab character when Tab is pressed
+    pub fn insert_spaces(&self, insert_spaces: bool) -> &Self {
+        self.insert_spaces.set(insert_spaces);
+        self
+    }
+
+    pub fn tab_width(&self, tab_width: u32) -> &Self {
+        self.text_box.tab_width(tab_width);
+        self
+    }
+
+    pub fn bracket_matching(&self, bracket_matching: bool) -> &Self {
+        self.bracket_matching.set(bracket_matching);
+        self
+    }
+
+    /// Text inserted for one level of indentation
+    fn indent_unit(&self) -> String {
+        if self.insert_spaces.get() {
+            " ".repeat(self.text_box.tab_width.get() as usize)
+        } else {
+            "\t".to_string()
+        }
+    }
+
+    fn tokens(&self, text: &str) -> Vec<Token> {
+        match *self.tokenizer.borrow() {
+            Some(ref tokenizer) => tokenizer.tokenize(text),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the bracket at or before the caret, and the one matching it
+    fn matching_brackets(&self, text: &str, tokens: &[Token]) -> Option<(usize, usize)> {
+        // Brackets inside strings and comments are ignored
+        let brackets: Vec<(usize, char)> = if self.tokenizer.borrow().is_some() {
+            tokens.iter()
+                .filter(|token| token.kind == TokenKind::Punctuation)
+                .filter_map(|token| text[token.start..].chars().next().map(|c| (token.start, c)))
+                .filter(|&(_, c)| opening(c).is_some() || closing(c).is_some())
+                .collect()
+        } else {
+            text.char_indices()
+                .filter(|&(_, c)| opening(c).is_some() || closing(c).is_some())
+                .collect()
+        };
+
+        let text_i = self.text_box.text_i.get();
+        let at = brackets.iter().position(|&(i, _)| i == text_i)
+            .or_else(|| brackets.iter().position(|&(i, _)| i + 1 == text_i))?;
+
+        let (start, c) = brackets[at];
+        let mut depth = 0;
+        if let Some(close) = closing(c) {
+            for &(i, other) in brackets[at + 1..].iter() {
+                if other == c {
+                    depth += 1;
+                } else if other == close {
+                    if depth == 0 {
+                        return Some((start, i));
+                    }
+                    depth -= 1;
+                }
+            }
+        } else if let Some(open) = opening(c) {
+            for &(i, other) in brackets[..at].iter().rev() {
+                if other == c {
+                    depth += 1;
+                } else if other == open {
+                    if depth == 0 {
+                        return Some((i, start));
+                    }
+                    depth -= 1;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Spans coloring the tokens of the text and the brackets matching at the caret
+    /// The text is only tokenized again when it changed since the last call.
+    fn spans(&self, text: &str) -> Vec<Span> {
+        let stale = self.highlight.borrow().as_ref().map_or(true, |highlight| highlight.text != text);
+        if stale {
+            let tokens = self.tokens(text);
+            let spans = {
+                let colors = self.colors.borrow();
+                tokens.iter()
+                    .filter_map(|token| colors.get(&token.kind).map(|&color| Span::new(token.start, token.end, Some(color), None)))
+                    .collect::<Vec<Span>>()
+            };
+            *self.highlight.borrow_mut() = Some(Highlight { text: text.to_string(), tokens: tokens, spans: spans });
+        }
+
+        let highlight = self.highlight.borrow();
+        let highlight = highlight.as_ref().unwrap();
+        let mut spans = highlight.spans.clone();
+
+        if self.bracket_matching.get() {
+            if let Some((open, close)) = self.matching_brackets(text, &highlight.tokens) {
+                spans.push(Span::new(open, open + 1, None, Some(self.bg_bracket)));
+                spans.push(Span::new(close, close + 1, None, Some(self.bg_bracket)));
+            }
+        }
+
+        spans
+    }
+
+    /// Start of the line holding the caret, and its leading whitespace
+    fn current_indent(&self) -> (usize, String) {
+        let text = self.text_box.text.borrow();
+        let text_i = self.text_box.text_i.get();
+        let line_start = text[..text_i].rfind('\n').map_or(0, |i| i + 1);
+        let indent = text[line_start..text_i].chars().take_while(|&c| c == ' ' || c == '\t').collect();
+        (line_start, indent)
+    }
+
+    /// Insert a newline, keeping the indentation of the current line
+    /// and adding a level after an opening bracket
+    fn newline(&self) {
+        let (_, mut indent) = self.current_indent();
+        let after_bracket = {
+            let text = self.text_box.text.borrow();
+            let text_i = self.text_box.text_i.get();
+            text[..text_i].chars().rev().skip_while(|&c| c == ' ' || c == '\t').next().and_then(closing).is_some()
+        };
+        if after_bracket {
+            indent.push_str(&self.indent_unit());
+        }
+        self.text_box.insert(&format!("\n{}", indent));
+    }
+
+    /// Remove one level of indentation before the caret, if the line holds nothing else
+    fn dedent(&self) {
+        let (line_start, indent) = self.current_indent();
+        let text_i = self.text_box.text_i.get();
+        if indent.is_empty() || line_start + indent.len() != text_i {
+            return;
+        }
+
+        let unit = self.indent_unit();
+        let remove = if indent.ends_with(&unit) {
+            unit.len()
+        } else if indent.ends_with('\t') {
+            1
+        } else {
+            indent.chars().rev().take_while(|&c| c == ' ').take(unit.len()).count()
+        };
+
+        self.text_box.replace_range(text_i - remove, text_i, "");
+    }
+}
+
+impl Place for CodeEditor {}
+
+impl Text for CodeEditor {
+    fn text<S: Into<String>>(&self, text: S) -> &Self {
+        self.text_box.text(text);
+        self
+    }
+
+    fn text_offset(&self, x: i32, y: i32) -> &Self {
+        self.text_box.text_offset(x, y);
+        self
+    }
+}
+
+impl Tooltip for CodeEditor {}
+
+impl Widget for CodeEditor {
+    fn rect(&self) -> &Cell<Rect> {
+        self.text_box.rect()
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        self.text_box.tooltip_text()
+    }
+
+    fn is_visible(&self) -> bool {
+        self.text_box.is_visible()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        let spans = self.spans(&self.text_box.text.borrow());
+        *self.text_box.spans.borrow_mut() = spans;
+        self.text_box.draw(renderer, focused);
+    }
+
+    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
+        if focused {
+            match event {
+                Event::Enter if self.auto_indent.get() => {
+                    self.newline();
+                    *redraw = true;
+                    return focused;
+                }
+                Event::Text { c: '\t' } if self.insert_spaces.get() => {
+                    self.text_box.insert(&self.indent_unit());
+                    *redraw = true;
+                    return focused;
+                }
+                Event::Text { c } if self.auto_indent.get() && opening(c).is_some() => {
+                    self.dedent();
+                }
+                _ => (),
+            }
+        }
+
+        self.text_box.event(event, focused, redraw)
+    }
+
+    fn visible(&self, flag: bool) {
+        self.text_box.visible(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("CodeEditor")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use event::Event;
+    use highlight::{Token, Tokenizer};
+    use traits::Text;
+    use widgets::Widget;
+    use super::CodeEditor;
+
+    /// Counts how often the text is tokenized
+    struct Counting(Rc<Cell<u32>>);
+
+    impl Tokenizer for Counting {
+        fn tokenize(&self, _text: &str) -> Vec<Token> {
+            self.0.set(self.0.get() + 1);
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn closing_brackets_dedent() {
+        let editor = CodeEditor::new();
+        editor.text("fn f() {\n    ");
+        let mut redraw = false;
+        editor.event(Event::Text { c: '}' }, true, &mut redraw);
+        assert_eq!(*editor.text_box.text.borrow(), "fn f() {\n}");
+        assert_eq!(editor.text_box.text_i.get(), 10);
+    }
+
+    #[test]
+    fn tokens_are_kept_until_the_text_changes() {
+        let count = Rc::new(Cell::new(0));
+        let editor = CodeEditor::new();
+        editor.tokenizer(Counting(count.clone()));
+        editor.spans("let a = 1;");
+        editor.spans("let a = 1;");
+        assert_eq!(count.get(), 1);
+        editor.spans("let a = 2;");
+        assert_eq!(count.get(), 2);
+    }
+}
@@ -3,16 +3,17 @@ use std::cell::{Cell, RefCell};
 //use std::cmp::{min, max};
 use std::sync::Arc;
 
-use cell::CheckSet;
+use cell::{CheckSet, CloneCell};
 use event::Event;
 use point::Point;
 use rect::Rect;
 use theme::{ITEM_BACKGROUND, ITEM_BORDER, ITEM_SELECTION};
-use traits::{Border, Click, Place};
+use traits::{Border, Click, Place, Tooltip};
 use widgets::Widget;
 
 pub struct ControlKnob {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Cell<Color>,
     pub fg: Cell<Color>,
     pub fg_border: Color,
@@ -30,6 +31,7 @@ impl ControlKnob {
     pub fn new() -> Arc<Self> {
         Arc::new(ControlKnob {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: Cell::new(ITEM_BACKGROUND),
             fg: Cell::new(ITEM_SELECTION),
             fg_border: ITEM_BORDER,
@@ -77,11 +79,21 @@ impl Click for ControlKnob {
 
 impl Place for ControlKnob {}
 
+impl Tooltip for ControlKnob {}
+
 impl Widget for ControlKnob {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         if self.visible.get(){
             let rect = self.rect.get();
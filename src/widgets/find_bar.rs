@@ -0,0 +1,296 @@
+use orbclient::{Color, Renderer};
+use std::cell::{Cell, RefCell};
+use std::sync::{Arc, Weak};
+
+use cell::{CheckSet, CloneCell};
+use event::Event;
+use point::Point;
+use rect::Rect;
+use theme::{BUTTON_BG_SELECTION, LABEL_BACKGROUND, LABEL_BORDER, WINDOW_BACKGROUND};
+use traits::{Border, Click, Enter, Place, Text, Tooltip};
+use widgets::{own_tooltip_at, Button, Label, Search, TextBox, Widget};
+
+/// A bar with find and replace controls for a text box
+pub struct FindBar {
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    pub fg_border: Color,
+    target: Arc<TextBox>,
+    pub query: Arc<TextBox>,
+    pub replacement: Arc<TextBox>,
+    case_toggle: Arc<Label>,
+    word_toggle: Arc<Label>,
+    previous: Arc<Button>,
+    next: Arc<Button>,
+    replace: Arc<Button>,
+    replace_all: Arc<Button>,
+    status: Arc<Label>,
+    widgets: RefCell<Vec<Arc<Widget>>>,
+    focused: Cell<Option<usize>>,
+    last_query: CloneCell<String>,
+    pub case_sensitive: Cell<bool>,
+    pub whole_word: Cell<bool>,
+    pub visible: Cell<bool>,
+}
+
+impl FindBar {
+    pub fn new(target: &Arc<TextBox>) -> Arc<Self> {
+        let case_toggle = Label::new();
+        case_toggle.text("Aa").border(true);
+        let word_toggle = Label::new();
+        word_toggle.text("W").border(true);
+
+        let previous = Button::new();
+        previous.text("<");
+        let next = Button::new();
+        next.text(">");
+        let replace = Button::new();
+        replace.text("Replace");
+        let replace_all = Button::new();
+        replace_all.text("All");
+
+        let find_bar = Arc::new(FindBar {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: WINDOW_BACKGROUND,
+            fg_border: LABEL_BORDER,
+            target: target.clone(),
+            query: TextBox::new(),
+            replacement: TextBox::new(),
+            case_toggle: case_toggle,
+            word_toggle: word_toggle,
+            previous: previous,
+            next: next,
+            replace: replace,
+            replace_all: replace_all,
+            status: Label::new(),
+            widgets: RefCell::new(Vec::new()),
+            focused: Cell::new(None),
+            last_query: CloneCell::new(String::new()),
+            case_sensitive: Cell::new(false),
+            whole_word: Cell::new(false),
+            visible: Cell::new(true),
+        });
+
+        {
+            let mut widgets = find_bar.widgets.borrow_mut();
+            widgets.push(find_bar.query.clone());
+            widgets.push(find_bar.case_toggle.clone());
+            widgets.push(find_bar.word_toggle.clone());
+            widgets.push(find_bar.previous.clone());
+            widgets.push(find_bar.next.clone());
+            widgets.push(find_bar.replacement.clone());
+            widgets.push(find_bar.replace.clone());
+            widgets.push(find_bar.replace_all.clone());
+            widgets.push(find_bar.status.clone());
+        }
+
+        // Callbacks hold weak references, as the bar owns the widgets calling them
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.query.on_enter(move |_| FindBar::with(&bar, |bar| { bar.find_next(); }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.replacement.on_enter(move |_| FindBar::with(&bar, |bar| { bar.replace(); }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.previous.on_click(move |_, _| FindBar::with(&bar, |bar| { bar.find_previous(); }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.next.on_click(move |_, _| FindBar::with(&bar, |bar| { bar.find_next(); }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.replace.on_click(move |_, _| FindBar::with(&bar, |bar| { bar.replace(); }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.replace_all.on_click(move |_, _| FindBar::with(&bar, |bar| { bar.replace_all(); }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.case_toggle.on_click(move |_, _| FindBar::with(&bar, |bar| {
+            bar.case_sensitive.set(!bar.case_sensitive.get());
+            bar.update();
+        }));
+        let bar = Arc::downgrade(&find_bar);
+        find_bar.word_toggle.on_click(move |_, _| FindBar::with(&bar, |bar| {
+            bar.whole_word.set(!bar.whole_word.get());
+            bar.update();
+        }));
+
+        find_bar.arrange();
+        find_bar
+    }
+
+    fn with<F: Fn(&FindBar)>(bar: &Weak<FindBar>, func: F) {
+        if let Some(bar) = bar.upgrade() {
+            func(&bar);
+        }
+    }
+
+    /// The search described by the controls
+    pub fn search(&self) -> Search {
+        Search::new(self.query.text.get())
+            .case_sensitive(self.case_sensitive.get())
+            .whole_word(self.whole_word.get())
+    }
+
+    /// Apply the search to the text box and refresh the controls
+    pub fn update(&self) {
+        let search = self.search();
+        let count = search.find_all(&self.target.text.borrow()).len();
+        if search.pattern.is_empty() {
+            self.status.text("");
+            self.target.search(None);
+        } else {
+            self.status.text(format!("{} matches", count));
+            self.target.search(Some(search));
+        }
+
+        for &(ref toggle, on) in [(&self.case_toggle, self.case_sensitive.get()), (&self.word_toggle, self.whole_word.get())].iter() {
+            toggle.bg.set(if on { BUTTON_BG_SELECTION } else { LABEL_BACKGROUND });
+        }
+    }
+
+    pub fn find_next(&self) -> bool {
+        self.update();
+        self.target.find_next()
+    }
+
+    pub fn find_previous(&self) -> bool {
+        self.update();
+        self.target.find_previous()
+    }
+
+    pub fn replace(&self) -> bool {
+        self.update();
+        let found = self.target.replace(&self.replacement.text.get());
+        self.update();
+        found
+    }
+
+    pub fn replace_all(&self) -> usize {
+        self.update();
+        let count = self.target.replace_all(&self.replacement.text.get());
+        self.update();
+        count
+    }
+
+    /// Lay out the controls in a row
+    pub fn arrange(&self) {
+        let rect = self.rect.get();
+        let height = if rect.height > 4 { rect.height - 4 } else { 0 };
+        let text_y = (height as i32 - 16) / 2;
+
+        let mut x = rect.x + 4;
+        let y = rect.y + 2;
+        let widths = [160, 28, 28, 28, 28, 120, 72, 40];
+        for (widget, &width) in self.widgets.borrow().iter().zip(widths.iter()) {
+            widget.rect().set(Rect::new(x, y, width, height));
+            x += width as i32 + 4;
+        }
+        self.status.position(x, y).size(if rect.x + rect.width as i32 > x { (rect.x + rect.width as i32 - x) as u32 } else { 0 }, height);
+
+        self.query.text_offset(4, text_y);
+        self.replacement.text_offset(4, text_y);
+        self.case_toggle.text_offset(6, text_y);
+        self.word_toggle.text_offset(10, text_y);
+        self.previous.text_offset(10, text_y);
+        self.next.text_offset(10, text_y);
+        self.replace.text_offset(8, text_y);
+        self.replace_all.text_offset(8, text_y);
+        self.status.text_offset(4, text_y);
+    }
+}
+
+impl Place for FindBar {
+    fn position(&self, x: i32, y: i32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.x = x;
+        rect.y = y;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+
+    fn size(&self, width: u32, height: u32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.width = width;
+        rect.height = height;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+}
+
+impl Tooltip for FindBar {}
+
+impl Widget for FindBar {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        if self.visible.get() {
+            let rect = self.rect.get();
+            renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+            renderer.rect(rect.x, rect.y, rect.width, 1, self.fg_border);
+
+            for (i, widget) in self.widgets.borrow().iter().enumerate() {
+                widget.draw(renderer, focused && self.focused.get() == Some(i));
+            }
+        }
+    }
+
+    fn event(&self, event: Event, mut focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        for (i, widget) in self.widgets.borrow().iter().enumerate() {
+            let is_focused = self.focused.get() == Some(i);
+            if widget.event(event, focused && is_focused, redraw) {
+                if self.focused.check_set(Some(i)) || ! focused {
+                    focused = true;
+                    *redraw = true;
+                }
+            } else if is_focused {
+                self.focused.set(None);
+            }
+        }
+
+        let query = self.query.text.get();
+        if query != self.last_query.get() {
+            self.last_query.set(query);
+            self.update();
+            *redraw = true;
+        }
+
+        focused
+    }
+
+    fn tooltip_at(&self, point: Point) -> Option<String> {
+        if !self.visible.get() || !self.rect.get().contains(point) {
+            return None;
+        }
+        self.widgets.borrow().iter().filter_map(|widget| widget.tooltip_at(point)).next()
+            .or_else(|| own_tooltip_at(self, point))
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+        if flag {
+            self.update();
+        } else {
+            self.target.search(None);
+        }
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("FindBar")
+    }
+}
@@ -3,14 +3,16 @@ use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use cell::CheckSet;
+use cell::{CheckSet, CloneCell};
 use event::Event;
+use point::Point;
 use rect::Rect;
-use traits::Place;
-use widgets::Widget;
+use traits::{Place, Tooltip};
+use widgets::{own_tooltip_at, Widget};
 
 pub struct Grid {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     space_x: Cell<i32>,
     space_y: Cell<i32>,
     entries: RefCell<BTreeMap<(usize, usize), Arc<Widget>>>,
@@ -22,6 +24,7 @@ impl Grid {
     pub fn new() -> Arc<Self> {
         Arc::new(Grid {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             space_x: Cell::new(0),
             space_y: Cell::new(0),
             entries: RefCell::new(BTreeMap::new()),
@@ -108,11 +111,21 @@ impl Place for Grid {
     }
 }
 
+impl Tooltip for Grid {}
+
 impl Widget for Grid {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         for (&(col, row), entry) in self.entries.borrow().iter() {
             entry.draw(renderer, self.focused.get() == Some((col, row)));
@@ -135,6 +148,14 @@ impl Widget for Grid {
         focused
     }
 
+    fn tooltip_at(&self, point: Point) -> Option<String> {
+        if !self.visible.get() || !self.rect.get().contains(point) {
+            return None;
+        }
+        self.entries.borrow().values().filter_map(|entry| entry.tooltip_at(point)).next()
+            .or_else(|| own_tooltip_at(self, point))
+    }
+
     fn visible(&self, flag: bool){
         self.visible.set(flag);
     }
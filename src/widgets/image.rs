@@ -4,14 +4,16 @@ use std::cell::{Cell, RefCell};
 use std::path::Path;
 use std::sync::Arc;
 
+use cell::CloneCell;
 use event::Event;
 use point::Point;
 use rect::Rect;
-use traits::{Click, Place};
+use traits::{Click, Place, Tooltip};
 use widgets::Widget;
 
 pub struct Image {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub image: RefCell<orbimage::Image>,
     click_callback: RefCell<Option<Arc<Fn(&Image, Point)>>>,
     pub visible: Cell<bool>,
@@ -29,6 +31,7 @@ impl Image {
     pub fn from_image(image: orbimage::Image) -> Arc<Self> {
         Arc::new(Image {
             rect: Cell::new(Rect::new(0, 0, image.width(), image.height())),
+            tooltip_text: CloneCell::new(String::new()),
             image: RefCell::new(image),
             click_callback: RefCell::new(None),
             visible: Cell::new(true),
@@ -55,11 +58,21 @@ impl Click for Image {
 
 impl Place for Image {}
 
+impl Tooltip for Image {}
+
 impl Widget for Image {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         if self.visible.get(){
             let rect = self.rect.get();
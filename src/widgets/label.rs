@@ -1,17 +1,20 @@
 use orbclient::{Color, Renderer};
 use std::cell::{Cell, RefCell};
+use std::cmp::max;
 use std::sync::Arc;
 
 use cell::{CloneCell, CheckSet};
 use event::Event;
 use point::Point;
 use rect::Rect;
+use text_layout::{is_rtl, layout_line};
 use theme::{LABEL_BACKGROUND, LABEL_BORDER, LABEL_FOREGROUND};
-use traits::{Border, Click, Place, Text};
+use traits::{Border, Click, Place, Text, Tooltip};
 use widgets::Widget;
 
 pub struct Label {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Cell<Color>,
     pub fg: Cell<Color>,
     pub fg_border: Cell<Color>,
@@ -28,6 +31,7 @@ impl Label {
     pub fn new() -> Arc<Self> {
         Arc::new(Label {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: Cell::new(LABEL_BACKGROUND),
             fg: Cell::new(LABEL_FOREGROUND),
             fg_border: Cell::new(LABEL_BORDER),
@@ -81,11 +85,21 @@ impl Text for Label {
     }
 }
 
+impl Tooltip for Label {}
+
 impl Widget for Label {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         if self.visible.get(){
             let rect = self.rect.get();
@@ -99,17 +113,27 @@ impl Widget for Label {
             let fg = self.fg.get();
             let text = self.text.borrow();
 
-            let mut point = self.text_offset.get();
-            for c in text.chars() {
-                if c == '\n' {
-                    point.x = self.text_offset.get().x;
-                    point.y += 16;
+            let text_offset = self.text_offset.get();
+            let mut y = text_offset.y;
+            for line in text.split('\n') {
+                let glyphs = layout_line(line, 0, line.len(), 1);
+                // Right to left lines are aligned right
+                let x = if is_rtl(line) {
+                    let width = glyphs.last().map_or(0, |glyph| glyph.x + glyph.width);
+                    max(text_offset.x, rect.width as i32 - text_offset.x - width)
                 } else {
-                    if point.x + 8 <= rect.width as i32 && point.y + 16 <= rect.height as i32 {
-                        renderer.char(point.x + rect.x, point.y + rect.y, c, fg);
+                    text_offset.x
+                };
+
+                for glyph in glyphs.iter() {
+                    let point = Point::new(x + glyph.x, y);
+                    if point.x + glyph.width <= rect.width as i32 && point.y + 16 <= rect.height as i32 {
+                        for c in line[glyph.start..glyph.end].chars() {
+                            renderer.char(point.x + rect.x, point.y + rect.y, c, fg);
+                        }
                     }
-                    point.x += 8;
                 }
+                y += 16;
             }
         }
     }
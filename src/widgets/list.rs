@@ -1,39 +1,51 @@
 use orbclient::{Renderer, Color};
 use orbimage;
 use std::cell::{ Cell, RefCell };
-use std::cmp;
+use std::cmp::{ self, Ordering };
+use std::collections::{ BTreeMap, HashMap };
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::{ Duration, Instant };
 
-use cell::CheckSet;
+use cell::{ CheckSet, CloneCell };
 use event::Event;
 use point::Point;
 use rect::Rect;
-use theme::{ ITEM_BACKGROUND, WINDOW_BACKGROUND, ITEM_SELECTION };
-use traits::{ Click, Place };
-use widgets::Widget;
-use std::ops::Index;
+use theme::{ ITEM_BACKGROUND, ITEM_HOVER, WINDOW_BACKGROUND, ITEM_SELECTION };
+use traits::{ Click, Place, Tooltip };
+use widgets::{ Orientation, ScrollBar, Widget };
+use widgets::selection::{ Selection, SelectionMode };
+use widgets::scroll_bar::SCROLL_BAR_SIZE;
 
 /// An entry in a list
 /// Each entry stores widgets within.
 pub struct Entry {
     pub height: Cell<u32>,
+    /// Text used to find the entry by typing, and by filters and sorting
+    pub text: CloneCell<String>,
     click_callback: RefCell<Option<Arc<Fn(&Entry, Point)>>>,
     widgets: RefCell<Vec<Arc<Widget>>>,
     pub highlight: Cell<Color>,
-    highlighted: Cell<bool>,
+    pub hover: Cell<Color>,
 }
 
 impl Entry {
     pub fn new(h: u32) -> Arc<Self> {
         Arc::new(Entry {
             height: Cell::new(h),
+            text: CloneCell::new(String::new()),
             click_callback: RefCell::new(None),
             widgets: RefCell::new(vec![]),
             highlight: Cell::new(ITEM_SELECTION),
-            highlighted: Cell::new(false),
+            hover: Cell::new(ITEM_HOVER),
         })
     }
 
+    pub fn text<S: Into<String>>(&self, text: S) -> &Self {
+        self.text.set(text.into());
+        self
+    }
+
     /// Adds a widget to the entry
     pub fn add<T: Widget>(&self, widget: &Arc<T>) {
         let mut widgets = self.widgets.borrow_mut();
@@ -58,50 +70,415 @@ impl Click for Entry {
     }
 }
 
+/// Identifies an entry independently of its position
+fn entry_id(entry: &Arc<Entry>) -> usize {
+    &**entry as *const Entry as usize
+}
+
+/// A scrollable list of entries
+/// Entries are either pushed one by one, or built on demand from a model
+/// with `List::model`, in which case only the visible rows are built.
+/// Pushed entries are shown through the filter and sort order, and indexes
+/// given to and returned by the list are positions among the shown entries.
+/// The filter and sort order don't apply to a model, which filters and sorts its own rows.
 pub struct List {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     v_scroll: Cell<i32>,
     current_height: Cell<u32>,
     entries: RefCell<Vec<Arc<Entry>>>,
+    shown: RefCell<Vec<Arc<Entry>>>,
+    filter: RefCell<Option<Arc<Fn(&Entry) -> bool>>>,
+    sort: RefCell<Option<Arc<Fn(&Entry, &Entry) -> Ordering>>>,
+    type_ahead: CloneCell<String>,
+    type_ahead_time: Cell<Option<Instant>>,
+    row_count: Cell<usize>,
+    row_height: Cell<u32>,
+    row_builder: RefCell<Option<Arc<Fn(&List, usize) -> Arc<Entry>>>>,
+    row_text: RefCell<Option<Arc<Fn(&List, usize) -> String>>>,
+    rows: RefCell<BTreeMap<usize, Arc<Entry>>>,
+    v_scroll_bar: Arc<ScrollBar>,
+    target: RefCell<Option<orbimage::Image>>,
+    row_image: RefCell<Option<orbimage::Image>>,
     pressed: Cell<bool>,
-    selected: Cell<Option<u32>>,
+    pub selection_mode: Cell<SelectionMode>,
+    selection: Selection,
+    hover: Cell<Option<usize>>,
+    selection_changed_callback: RefCell<Option<Arc<Fn(&List)>>>,
+    /// Whether entries can be dragged to reorder them
+    pub reorderable: Cell<bool>,
+    drag_from: Cell<Option<(usize, Point)>>,
+    dragging: Cell<bool>,
+    drop_gap: Cell<Option<usize>>,
+    reorder_callback: RefCell<Option<Arc<Fn(&List, usize, usize)>>>,
     pub visible: Cell<bool>,
 }
 
+/// Reuse an image if it has the requested size, as allocating one per frame is slow
+pub fn reuse_image(image: &mut Option<orbimage::Image>, width: u32, height: u32) -> &mut orbimage::Image {
+    let reuse = match *image {
+        Some(ref image) => image.width() == width && image.height() == height,
+        None => false,
+    };
+    if !reuse {
+        *image = Some(orbimage::Image::new(width, height));
+    }
+    image.as_mut().unwrap()
+}
+
 impl List {
     pub fn new() -> Arc<Self> {
         Arc::new(List {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             v_scroll: Cell::new(0),
             current_height: Cell::new(0),
             entries: RefCell::new(vec![]),
+            shown: RefCell::new(vec![]),
+            filter: RefCell::new(None),
+            sort: RefCell::new(None),
+            type_ahead: CloneCell::new(String::new()),
+            type_ahead_time: Cell::new(None),
+            row_count: Cell::new(0),
+            row_height: Cell::new(0),
+            row_builder: RefCell::new(None),
+            row_text: RefCell::new(None),
+            rows: RefCell::new(BTreeMap::new()),
+            v_scroll_bar: ScrollBar::new(Orientation::Vertical),
+            target: RefCell::new(None),
+            row_image: RefCell::new(None),
             pressed: Cell::new(false),
-            selected: Cell::new(None),
+            selection_mode: Cell::new(SelectionMode::Single),
+            selection: Selection::new(),
+            hover: Cell::new(None),
+            selection_changed_callback: RefCell::new(None),
+            reorderable: Cell::new(false),
+            drag_from: Cell::new(None),
+            dragging: Cell::new(false),
+            drop_gap: Cell::new(None),
+            reorder_callback: RefCell::new(None),
             visible: Cell::new(true),
         })
     }
 
     pub fn push(&self, entry: &Arc<Entry>) {
-        let h = entry.height.get();
         self.entries.borrow_mut().push(entry.clone());
-        self.current_height.set(self.current_height.get() + h);
+        self.update_shown();
     }
 
-    // Given absolute coordinates, returns the list entry index
-    // drawn at that point.
-    fn get_entry_index(&self, p: Point) -> Option<u32> {
-        if self.rect.get().contains(p) {
-            let mut current_y = 0;
-            let x = self.rect.get().x;
-            let y = self.rect.get().y;
-            let width = self.rect.get().width;
-            let scroll = self.v_scroll.get();
+    /// Insert an entry before the shown entry at the given index, or at the end if the index is past it
+    /// Entries in view stay in place, and selected entries stay selected.
+    pub fn insert(&self, index: usize, entry: &Arc<Entry>) {
+        let source_i = self.source_index(index).unwrap_or(self.entries.borrow().len());
+        self.entries.borrow_mut().insert(source_i, entry.clone());
+        self.update_shown();
+
+        let index = self.shown.borrow().iter().position(|other| Arc::ptr_eq(other, entry));
+        if let Some(index) = index {
+            let (y, h) = self.entry_span(index);
+            if y < self.v_scroll.get() {
+                self.scroll(h as i32);
+            }
+        }
+    }
+
+    /// Remove the shown entry at the given index
+    pub fn remove(&self, index: usize) -> Option<Arc<Entry>> {
+        let source_i = self.source_index(index)?;
+
+        let (y, h) = self.entry_span(index);
+        let entry = self.entries.borrow_mut().remove(source_i);
+        self.update_shown();
+
+        if y + (h as i32) <= self.v_scroll.get() {
+            self.scroll(-(h as i32));
+        } else {
+            self.scroll(0);
+        }
+
+        Some(entry)
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+        self.update_shown();
+        self.v_scroll.set(0);
+    }
+
+    /// Move a shown entry so that it ends up at index to, keeping its selection
+    /// When a sort order is set, the entry is sorted back into place.
+    pub fn move_entry(&self, from: usize, to: usize) {
+        let len = self.shown.borrow().len();
+        if from >= len || to >= len || from == to || self.is_model() {
+            return;
+        }
+
+        let (entry, target) = {
+            let shown = self.shown.borrow();
+            (shown[from].clone(), shown[to].clone())
+        };
+
+        {
+            let mut entries = self.entries.borrow_mut();
+            if let Some(source_i) = entries.iter().position(|other| Arc::ptr_eq(other, &entry)) {
+                entries.remove(source_i);
+            }
+            let target_i = entries.iter().position(|other| Arc::ptr_eq(other, &target)).unwrap_or(0);
+            entries.insert(if from < to { target_i + 1 } else { target_i }, entry);
+        }
+        self.update_shown();
+    }
 
-            for (i, entry) in self.entries.borrow().iter().enumerate() {
-                if Rect::new(x, y+current_y-scroll, width, entry.height.get()).contains(p) {
-                    return Some(i as u32)
+    /// Number of shown entries, or of rows when a model is used
+    pub fn entries_len(&self) -> usize {
+        self.len()
+    }
+
+    /// Show only the entries accepted by the predicate, which is ignored while a model is used
+    pub fn filter<F: Fn(&Entry) -> bool + 'static>(&self, func: F) -> &Self {
+        *self.filter.borrow_mut() = Some(Arc::new(func));
+        self.warn_model_order();
+        self.update_shown();
+        self
+    }
+
+    pub fn clear_filter(&self) -> &Self {
+        *self.filter.borrow_mut() = None;
+        self.update_shown();
+        self
+    }
+
+    /// Show the entries ordered by the comparator, instead of in the order they were added
+    /// The order is ignored while a model is used.
+    pub fn sort_by<F: Fn(&Entry, &Entry) -> Ordering + 'static>(&self, func: F) -> &Self {
+        *self.sort.borrow_mut() = Some(Arc::new(func));
+        self.warn_model_order();
+        self.update_shown();
+        self
+    }
+
+    pub fn clear_sort(&self) -> &Self {
+        *self.sort.borrow_mut() = None;
+        self.update_shown();
+        self
+    }
+
+    /// Warn in debug builds when a filter or sort order is set together with a model,
+    /// as model rows are built by index and never go through them
+    fn warn_model_order(&self) {
+        let ordered = self.filter.borrow().is_some() || self.sort.borrow().is_some();
+        if cfg!(debug_assertions) && ordered && self.is_model() {
+            println!("List filter and sort order are ignored while a model is used, filter and sort the model instead");
+        }
+    }
+
+    /// Index among all entries of the shown entry at index
+    fn source_index(&self, index: usize) -> Option<usize> {
+        let entry = self.shown.borrow().get(index).cloned()?;
+        self.entries.borrow().iter().position(|other| Arc::ptr_eq(other, &entry))
+    }
+
+    /// Apply the filter and sort order, keeping the selection on the same entries
+    fn update_shown(&self) {
+        let mut shown: Vec<Arc<Entry>> = match *self.filter.borrow() {
+            Some(ref filter) => self.entries.borrow().iter().filter(|entry| filter(entry)).cloned().collect(),
+            None => self.entries.borrow().clone(),
+        };
+        if let Some(ref sort) = *self.sort.borrow() {
+            shown.sort_by(|a, b| sort(a, b));
+        }
+
+        let positions: HashMap<usize, usize> = shown.iter().enumerate().map(|(i, entry)| (entry_id(entry), i)).collect();
+        let old_shown = self.shown.replace(shown);
+        self.update_height();
+        self.scroll(0);
+
+        if !self.is_model() {
+            self.remap(|i| old_shown.get(i).and_then(|entry| positions.get(&entry_id(entry)).cloned()));
+        }
+    }
+
+    fn update_height(&self) {
+        let height = self.shown.borrow().iter().map(|entry| entry.height.get()).sum();
+        self.current_height.set(height);
+    }
+
+    /// Jump to the first entry from the current one whose text starts with the typed text
+    /// Typing the same character again cycles through the entries starting with it.
+    /// A list using a model is only searched when it has `model_text`.
+    fn type_ahead(&self, c: char) -> bool {
+        if self.is_model() && self.row_text.borrow().is_none() {
+            return false;
+        }
+
+        let mut typed = if self.typing() { self.type_ahead.get() } else { String::new() };
+        self.type_ahead_time.set(Some(Instant::now()));
+
+        let repeat = typed.chars().all(|typed_c| typed_c == c);
+        typed.extend(c.to_lowercase());
+        self.type_ahead.set(typed.clone());
+        let prefix = if repeat { c.to_lowercase().collect() } else { typed };
+
+        let len = self.len();
+        let start = match self.selection.current.get() {
+            Some(i) if repeat => i + 1,
+            Some(i) => i,
+            None => 0,
+        };
+        for offset in 0..len {
+            let i = (start + offset) % len;
+            let matches = self.entry_text(i).map_or(false, |text| text.to_lowercase().starts_with(&prefix));
+            if matches {
+                self.activate(i, true);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether a character was typed to find an entry recently
+    fn typing(&self) -> bool {
+        self.type_ahead_time.get().map_or(false, |time| time.elapsed() < Duration::from_millis(1000))
+    }
+
+    /// Text of an entry, given by `model_text` for a list using a model
+    fn entry_text(&self, i: usize) -> Option<String> {
+        if !self.is_model() {
+            return self.shown.borrow().get(i).map(|entry| entry.text.get());
+        }
+        let row_text = self.row_text.borrow().clone();
+        match row_text {
+            Some(row_text) => if i < self.len() { Some(row_text(self, i)) } else { None },
+            None => None,
+        }
+    }
+
+    /// Update the selection and other entry indexes after entries were moved
+    fn remap<F: Fn(usize) -> Option<usize>>(&self, f: F) {
+        self.hover.set(self.hover.get().and_then(&f));
+        if self.selection.remap(f) {
+            self.emit_selection_changed();
+        }
+    }
+
+    /// Show count rows of the same height, built by the builder when they become visible
+    /// Pushed entries are no longer shown.
+    pub fn model<F: Fn(&List, usize) -> Arc<Entry> + 'static>(&self, count: usize, row_height: u32, builder: F) -> &Self {
+        *self.row_builder.borrow_mut() = Some(Arc::new(builder));
+        self.warn_model_order();
+        self.row_height.set(row_height);
+        self.row_count(count);
+        self
+    }
+
+    /// Give the text of each row of the model, searched by typing without building the rows
+    pub fn model_text<F: Fn(&List, usize) -> String + 'static>(&self, func: F) -> &Self {
+        *self.row_text.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    /// Change the number of rows of the model
+    pub fn row_count(&self, count: usize) -> &Self {
+        self.row_count.set(count);
+        self.refresh();
+        self.remap(|i| if i < count { Some(i) } else { None });
+        self.scroll(0);
+        self
+    }
+
+    /// Forget the rows built from the model, so they are built again with new data
+    pub fn refresh(&self) {
+        self.rows.borrow_mut().clear();
+    }
+
+    fn is_model(&self) -> bool {
+        self.row_builder.borrow().is_some()
+    }
+
+    fn len(&self) -> usize {
+        if self.is_model() {
+            self.row_count.get()
+        } else {
+            self.shown.borrow().len()
+        }
+    }
+
+    fn content_height(&self) -> u32 {
+        if self.is_model() {
+            self.row_count.get() as u32 * self.row_height.get()
+        } else {
+            self.current_height.get()
+        }
+    }
+
+    /// Get an entry, building it from the model if needed
+    fn entry(&self, i: usize) -> Option<Arc<Entry>> {
+        if i >= self.len() {
+            return None;
+        }
+
+        let builder = self.row_builder.borrow().clone();
+        match builder {
+            Some(builder) => {
+                if let Some(entry) = self.rows.borrow().get(&i) {
+                    return Some(entry.clone());
                 }
-                current_y += entry.height.get() as i32
+                let entry = builder(self, i);
+                self.rows.borrow_mut().insert(i, entry.clone());
+                Some(entry)
+            },
+            None => self.shown.borrow().get(i).cloned(),
+        }
+    }
+
+    /// Vertical offset and height of an entry, relative to the top of the list
+    fn entry_span(&self, i: usize) -> (i32, u32) {
+        if self.is_model() {
+            let h = self.row_height.get();
+            (i as i32 * h as i32, h)
+        } else {
+            let entries = self.shown.borrow();
+            let y = entries.iter().take(i).map(|entry| entry.height.get() as i32).sum();
+            (y, entries.get(i).map_or(0, |entry| entry.height.get()))
+        }
+    }
+
+    /// Indexes of the entries overlapping a vertical range, relative to the top of the list
+    fn entries_between(&self, top: i32, bottom: i32) -> Range<usize> {
+        if self.is_model() {
+            let h = cmp::max(1, self.row_height.get()) as i32;
+            let start = cmp::max(0, top / h) as usize;
+            let end = cmp::max(0, (bottom + h - 1) / h) as usize;
+            cmp::min(start, self.len())..cmp::min(end, self.len())
+        } else {
+            let mut start = None;
+            let mut end = 0;
+            let mut y = 0;
+            for (i, entry) in self.shown.borrow().iter().enumerate() {
+                if y >= bottom {
+                    break;
+                }
+                let h = entry.height.get() as i32;
+                if y + h > top && start.is_none() {
+                    start = Some(i);
+                }
+                y += h;
+                end = i + 1;
+            }
+            start.unwrap_or(end)..end
+        }
+    }
+
+    // Given absolute coordinates, returns the list entry index
+    // drawn at that point.
+    fn get_entry_index(&self, p: Point) -> Option<usize> {
+        let rect = self.rect.get();
+        if rect.contains(p) {
+            let y = p.y - rect.y + self.v_scroll.get();
+            let range = self.entries_between(y, y + 1);
+            if range.start < range.end {
+                return Some(range.start);
             }
         }
 
@@ -111,7 +488,7 @@ impl List {
     pub fn scroll(&self, y: i32) {
         let mut set_to = self.v_scroll.get() + y;
 
-        let max = cmp::max(0, self.current_height.get() as i32 - self.rect.get().height as i32);
+        let max = cmp::max(0, self.content_height() as i32 - self.rect.get().height as i32);
         if set_to < 0 {
             set_to = 0;
         } else if set_to > max {
@@ -121,89 +498,308 @@ impl List {
         self.v_scroll.set(set_to);
     }
 
-    fn change_selection(&self, i: u32) {
-        match self.selected.get() {
-            Some(i) => {
-                match self.entries.borrow().get(i as usize) {
-                    Some(entry) => {
-                        entry.highlighted.set(false);
-                    },
-                    None => {},
-                }
-            },
-            _ => {},
+    pub fn selection_mode(&self, mode: SelectionMode) -> &Self {
+        self.selection_mode.set(mode);
+        if mode == SelectionMode::Single {
+            if self.selection.single(Some) {
+                self.emit_selection_changed();
+            }
         }
+        self
+    }
 
-        if let Some(entry) = self.entries.borrow().get(i as usize) {
-            entry.highlighted.set(true);
-            self.selected.set(Some(i));
+    pub fn on_selection_changed<T: Fn(&Self) + 'static>(&self, func: T) -> &Self {
+        *self.selection_changed_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
 
-            let mut y = 0;
+    pub fn emit_selection_changed(&self) {
+        let callback = self.selection_changed_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self);
+        }
+    }
+
+    /// The entry moved to with the keyboard or last clicked
+    pub fn selected(&self) -> Option<usize> {
+        self.selection.current.get()
+    }
+
+    pub fn selected_indices(&self) -> Vec<usize> {
+        self.selection.keys()
+    }
+
+    pub fn is_selected(&self, i: usize) -> bool {
+        self.selection.contains(i)
+    }
+
+    /// Select only the given entry, and scroll it into view
+    pub fn select(&self, i: usize) {
+        if i < self.len() {
+            self.change_current(i);
+            if self.selection.select(i, i) {
+                self.emit_selection_changed();
+            }
+        }
+    }
+
+    pub fn select_all(&self) {
+        if self.selection_mode.get() != SelectionMode::Single && self.selection.set((0..self.len()).collect()) {
+            self.emit_selection_changed();
+        }
+    }
+
+    pub fn clear_selection(&self) {
+        if self.selection.set(Default::default()) {
+            self.emit_selection_changed();
+        }
+    }
+
+    /// Select an entry that was clicked or moved to, following the selection mode
+    fn activate(&self, i: usize, key: bool) {
+        self.change_current(i);
+        if self.selection.activate(self.selection_mode.get(), i, key, Some) {
+            self.emit_selection_changed();
+        }
+    }
+
+    pub fn reorderable(&self, reorderable: bool) -> &Self {
+        self.reorderable.set(reorderable);
+        self
+    }
+
+    /// Called after an entry was dragged from one index to another
+    /// Pushed entries are already moved, while a model has to move its rows itself.
+    pub fn on_reorder<T: Fn(&Self, usize, usize) + 'static>(&self, func: T) -> &Self {
+        *self.reorder_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_reorder(&self, from: usize, to: usize) {
+        let callback = self.reorder_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self, from, to);
+        }
+    }
+
+    /// Index of the gap between entries closest to a point, where a dragged entry is dropped
+    fn drop_gap_at(&self, point: Point) -> usize {
+        let y = point.y - self.rect.get().y + self.v_scroll.get();
+        let range = self.entries_between(y, y + 1);
+        if range.start < range.end {
+            let (entry_y, h) = self.entry_span(range.start);
+            if y - entry_y < h as i32 / 2 {
+                range.start
+            } else {
+                range.start + 1
+            }
+        } else if y < 0 {
+            0
+        } else {
+            self.len()
+        }
+    }
+
+    /// Follow a dragged entry, returning true while an entry is dragged
+    fn drag(&self, point: Point, left_button: bool, redraw: &mut bool) -> bool {
+        let (from, origin) = match self.drag_from.get() {
+            Some(drag_from) => drag_from,
+            None => return false,
+        };
+
+        if !left_button {
+            self.drag_from.set(None);
+            let gap = self.drop_gap.take();
+            if self.dragging.check_set(false) {
+                self.pressed.set(false);
+                *redraw = true;
+                if let Some(gap) = gap {
+                    let to = if gap > from { gap - 1 } else { gap };
+                    if to != from {
+                        if !self.is_model() {
+                            self.move_entry(from, to);
+                        }
+                        self.emit_reorder(from, to);
+                    }
+                }
+                return true;
+            }
+            return false;
+        }
 
-            for e in self.entries.borrow().index(0..(i as usize)) {
-                y += e.height.get();
+        if !self.dragging.get() {
+            if (point.x - origin.x).abs() + (point.y - origin.y).abs() < 4 {
+                return false;
             }
+            self.dragging.set(true);
+        }
+
+        // Scroll when dragging near the edges
+        let rect = self.rect.get();
+        if point.y < rect.y + 16 {
+            self.scroll(-cmp::min(16, rect.y + 16 - point.y));
+        } else if point.y > rect.y + rect.height as i32 - 16 {
+            self.scroll(cmp::min(16, point.y - (rect.y + rect.height as i32 - 16)));
+        }
+
+        self.drop_gap.set(Some(self.drop_gap_at(point)));
+        *redraw = true;
+        true
+    }
+
+    /// Whether the content is taller than the list, so a scroll bar is shown
+    fn has_scroll_bar(&self) -> bool {
+        self.content_height() > self.rect.get().height
+    }
+
+    /// Width left to the entries next to the scroll bar
+    fn entry_width(&self) -> u32 {
+        let width = self.rect.get().width;
+        if self.has_scroll_bar() {
+            cmp::max(width, SCROLL_BAR_SIZE) - SCROLL_BAR_SIZE
+        } else {
+            width
+        }
+    }
+
+    fn update_scroll_bar(&self) {
+        let rect = self.rect.get();
+        self.v_scroll_bar.rect.set(Rect::new(
+            rect.x + rect.width as i32 - SCROLL_BAR_SIZE as i32, rect.y,
+            SCROLL_BAR_SIZE, rect.height
+        ));
+        self.v_scroll_bar.range(self.content_height(), rect.height).value(self.v_scroll.get());
+    }
 
+    /// Move the keyboard focus to an entry and scroll it into view
+    fn change_current(&self, i: usize) {
+        if i < self.len() {
+            self.selection.current.set(Some(i));
+
+            let (y, h) = self.entry_span(i);
             let v_scroll = self.v_scroll.get();
+            let height = self.rect.get().height as i32;
 
-            if y < v_scroll as u32 {
-                self.scroll(y as i32 - v_scroll);
-            } else if (y + entry.height.get() as u32) > (v_scroll as u32 + self.rect.get().height) {
-                self.scroll((y + entry.height.get()) as i32 - (v_scroll + self.rect.get().height as i32));
+            if y < v_scroll {
+                self.scroll(y - v_scroll);
+            } else if y + h as i32 > v_scroll + height {
+                self.scroll(y + h as i32 - (v_scroll + height));
             }
         }
     }
 }
 
+impl Tooltip for List {}
+
 impl Widget for List {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
-    fn draw(&self, renderer: &mut Renderer, _focused: bool) {
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
         if self.visible.get(){
-            let mut current_y = 0;
             let x = self.rect.get().x;
             let y = self.rect.get().y;
             let width = self.rect.get().width;
             let height = self.rect.get().height;
+            let v_scroll = self.v_scroll.get();
+            let entry_width = self.entry_width();
 
-            let mut target = orbimage::Image::new(width, height);
+            let mut target = self.target.borrow_mut();
+            let target = reuse_image(&mut target, width, height);
             target.set(WINDOW_BACKGROUND);
 
-            for entry in self.entries.borrow().iter() {
-                let mut image = orbimage::Image::new(width, entry.height.get());
+            // Only the visible entries are drawn
+            let visible = self.entries_between(v_scroll, v_scroll + height as i32);
+            let mut current_y = self.entry_span(visible.start).0;
+            for i in visible.clone() {
+                let entry = match self.entry(i) {
+                    Some(entry) => entry,
+                    None => break,
+                };
+
+                let mut image = self.row_image.borrow_mut();
+                let image = reuse_image(&mut image, entry_width, entry.height.get());
 
-                if entry.highlighted.get() {
+                if self.is_selected(i) {
                     image.set(entry.highlight.get());
+                } else if self.hover.get() == Some(i) {
+                    image.set(entry.hover.get());
                 } else {
                     image.set(ITEM_BACKGROUND);
                 }
 
                 for widget in entry.widgets().borrow().iter() {
-                    widget.draw(&mut image, false)
+                    widget.draw(image, false)
                 }
 
-            let image = image.data();
-            target.image(0, current_y-self.v_scroll.get(), width, entry.height.get(), &image);
+                // Outline the entry moved to with the keyboard, when it may differ from the selection
+                if focused && self.selection_mode.get() != SelectionMode::Single && self.selection.current.get() == Some(i) {
+                    let (w, h) = (image.width(), image.height());
+                    let color = entry.highlight.get();
+                    image.rect(0, 0, w, 1, color);
+                    image.rect(0, h as i32 - 1, w, 1, color);
+                    image.rect(0, 0, 1, h, color);
+                    image.rect(w as i32 - 1, 0, 1, h, color);
+                }
+
+                target.image(0, current_y - v_scroll, entry_width, entry.height.get(), &image.data());
 
                 current_y += entry.height.get() as i32
             }
-            let target = target.data();
-            renderer.image(x, y, width, height, &target)
+
+            if let Some(gap) = self.drop_gap.get() {
+                let gap_y = if gap < self.len() { self.entry_span(gap).0 } else { self.content_height() as i32 };
+                target.rect(0, gap_y - v_scroll - 1, entry_width, 2, ITEM_SELECTION);
+            }
+
+            // Rows scrolled out of view are built again when needed
+            self.rows.borrow_mut().retain(|i, _| visible.start <= *i && *i < visible.end);
+
+            renderer.image(x, y, width, height, &target.data());
+
+            if self.has_scroll_bar() {
+                self.update_scroll_bar();
+                self.v_scroll_bar.draw(renderer, false);
+            }
         }
     }
 
-    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
+    fn event(&self, event: Event, mut focused: bool, redraw: &mut bool) -> bool {
         if self.visible.get(){
             match event {
                 Event::Mouse { point, left_button, .. } => {
+                    if self.has_scroll_bar() {
+                        self.update_scroll_bar();
+                        if self.v_scroll_bar.event(event, false, redraw) {
+                            self.v_scroll.set(self.v_scroll_bar.value.get());
+                            if self.hover.check_set(None) {
+                                *redraw = true;
+                            }
+                            return focused;
+                        }
+                    }
+
+                    if self.drag(point, left_button, redraw) {
+                        return focused;
+                    }
+
                     let mut click = false;
+                    let mut press = false;
 
                     let rect = self.rect.get();
                     if rect.contains(point) {
                         if left_button {
                             if self.pressed.check_set(true) {
+                                press = true;
                                 *redraw = true;
                             }
                         } else {
@@ -220,67 +816,82 @@ impl Widget for List {
                         }
                     }
 
-                    if let Some(i) = self.get_entry_index(point) {
-                        if click {
-                            if let Some(entry) = self.entries.borrow().get(i as usize) {
-                                entry.emit_click(point);
+                    let index = self.get_entry_index(point);
+                    if self.hover.check_set(index) {
+                        *redraw = true;
+                    }
+
+                    if press {
+                        focused = true;
+                    }
+
+                    if let Some(i) = index {
+                        if press {
+                            self.activate(i, false);
+                            if self.reorderable.get() {
+                                self.drag_from.set(Some((i, point)));
                             }
                         }
 
-                        match self.selected.get() {
-                            None => {
-                                self.change_selection(i);
-                                *redraw = true;
-                            },
-                            Some(selected) => {
-                                if selected != i {
-                                    self.change_selection(i);
-                                    *redraw = true;
-                                }
-                            },
+                        if click {
+                            if let Some(entry) = self.entry(i) {
+                                entry.emit_click(point);
+                            }
                         }
                     }
                 },
-                Event::UpArrow => {
-                    match self.selected.get() {
-                        None => {
-                            self.change_selection(0);
-                            *redraw = true;
+                Event::Modifiers { ctrl, shift, .. } => {
+                    self.selection.modifiers(ctrl, shift);
+                },
+                Event::UpArrow if focused => {
+                    match self.selection.current.get() {
+                        None => self.activate(0, true),
+                        Some(i) => if i > 0 {
+                            self.activate(i - 1, true);
                         },
-                        Some(i) => {
-                            if i > 0 {
-                                self.change_selection(i - 1);
-                                *redraw = true;
-                            }
-                        }
                     }
+                    *redraw = true;
                 },
-                Event::DownArrow => {
-                    match self.selected.get() {
-                        None => {
-                            self.change_selection(0);
-                            *redraw = true;
+                Event::DownArrow if focused => {
+                    match self.selection.current.get() {
+                        None => self.activate(0, true),
+                        Some(i) => if i + 1 < self.len() {
+                            self.activate(i + 1, true);
                         },
-                        Some(i) => {
-                            if i < self.entries.borrow().len() as u32 - 1 {
-                                self.change_selection(i + 1);
-                                *redraw = true;
-                            }
-                        }
                     }
+                    *redraw = true;
                 },
-                Event::Home => {
-                    self.change_selection(0);
+                Event::Home if focused => {
+                    self.activate(0, true);
                     *redraw = true
                 },
-                Event::End => {
-                    self.change_selection(self.entries.borrow().len() as u32 - 1);
-                    *redraw = true
+                Event::End if focused => {
+                    if self.len() > 0 {
+                        self.activate(self.len() - 1, true);
+                        *redraw = true
+                    }
                 },
-                Event::Enter => {
-                    match self.selected.get() {
+                Event::Text { c: ' ' } if focused && !self.typing() => {
+                    if self.selection.current.get().is_some() {
+                        if self.selection.toggle_current(self.selection_mode.get(), Some) {
+                            self.emit_selection_changed();
+                        }
+                        *redraw = true;
+                    }
+                },
+                Event::Text { c } if focused && !c.is_control() => {
+                    if self.type_ahead(c) {
+                        *redraw = true;
+                    }
+                },
+                Event::Shortcut { c: 'a', ctrl: true, .. } if focused => {
+                    self.select_all();
+                    *redraw = true;
+                },
+                Event::Enter if focused => {
+                    match self.selection.current.get() {
                         Some(i) => {
-                            match self.entries.borrow().get(i as usize) {
+                            match self.entry(i) {
                                 Some(entry) => {
                                     entry.emit_click(Point { x: 0, y: 0});
                                 },
@@ -299,7 +910,7 @@ impl Widget for List {
         }
         focused
     }
-    
+
     fn visible(&self, flag: bool){
         self.visible.set(flag);
     }
@@ -310,3 +921,108 @@ impl Widget for List {
 }
 
 impl Place for List {}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::Arc;
+
+    use event::Event;
+    use traits::Place;
+    use widgets::Widget;
+    use super::{Entry, List};
+
+    fn list(texts: &[&str]) -> Arc<List> {
+        let list = List::new();
+        list.size(100, 100);
+        for text in texts.iter() {
+            let entry = Entry::new(20);
+            entry.text(*text);
+            list.push(&entry);
+        }
+        list
+    }
+
+    fn shown(list: &List) -> Vec<String> {
+        (0..list.entries_len()).filter_map(|i| list.entry_text(i)).collect()
+    }
+
+    fn type_text(list: &List, text: &str) {
+        let mut redraw = false;
+        for c in text.chars() {
+            list.event(Event::Text { c: c }, true, &mut redraw);
+        }
+    }
+
+    #[test]
+    fn filter_and_sort() {
+        let list = list(&["pear", "apple", "fig", "banana"]);
+        list.filter(|entry| entry.text.get().len() > 3);
+        assert_eq!(shown(&list), vec!["pear", "apple", "banana"]);
+        list.sort_by(|a, b| a.text.get().cmp(&b.text.get()));
+        assert_eq!(shown(&list), vec!["apple", "banana", "pear"]);
+        list.clear_filter();
+        assert_eq!(shown(&list), vec!["apple", "banana", "fig", "pear"]);
+        list.clear_sort();
+        assert_eq!(shown(&list), vec!["pear", "apple", "fig", "banana"]);
+    }
+
+    #[test]
+    fn selection_follows_shown_entries() {
+        let list = list(&["pear", "apple", "fig", "banana"]);
+        list.select(0);
+        list.sort_by(|a, b| a.text.get().cmp(&b.text.get()));
+        assert_eq!(list.selected_indices(), vec![3]);
+        assert_eq!(list.selected(), Some(3));
+
+        list.filter(|entry| entry.text.get() != "pear");
+        assert_eq!(list.selected_indices(), Vec::<usize>::new());
+        assert_eq!(list.selected(), None);
+    }
+
+    #[test]
+    fn type_ahead_finds_prefixes() {
+        let list = list(&["apple", "avocado", "banana", "Blueberry"]);
+        type_text(&list, "b");
+        assert_eq!(list.selected(), Some(2));
+        type_text(&list, "l");
+        assert_eq!(list.selected(), Some(3));
+        assert_eq!(list.selected_indices(), vec![3]);
+    }
+
+    #[test]
+    fn type_ahead_cycles_on_repeats() {
+        let list = list(&["apple", "avocado", "banana"]);
+        type_text(&list, "a");
+        assert_eq!(list.selected(), Some(0));
+        type_text(&list, "a");
+        assert_eq!(list.selected(), Some(1));
+        type_text(&list, "a");
+        assert_eq!(list.selected(), Some(0));
+        // Nothing starts with the typed text
+        type_text(&list, "x");
+        assert_eq!(list.selected(), Some(0));
+    }
+
+    #[test]
+    fn type_ahead_searches_model_text() {
+        let fruits = vec!["apple", "avocado", "banana", "cherry"];
+        let built = Arc::new(Cell::new(0));
+        let built_clone = built.clone();
+        let list = List::new();
+        list.size(100, 100);
+        list.model(fruits.len(), 20, move |_, _| {
+            built_clone.set(built_clone.get() + 1);
+            Entry::new(20)
+        });
+
+        // Without model_text, typing does not search
+        type_text(&list, "b");
+        assert_eq!(list.selected(), None);
+
+        list.model_text(move |_, i| fruits[i].to_string());
+        type_text(&list, "ch");
+        assert_eq!(list.selected(), Some(3));
+        assert_eq!(built.get(), 0);
+    }
+}
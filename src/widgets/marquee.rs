@@ -7,11 +7,12 @@ use event::Event;
 use point::Point;
 use rect::Rect;
 use theme::{ BUTTON_BG_SELECTION, BUTTON_FOREGROUND, BUTTON_FG_SELECTION};
-use traits::{Border, Click, Place, Text};
+use traits::{Border, Click, Place, Text, Tooltip};
 use widgets::Widget;
 
 pub struct Marquee {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Cell<Color>,
     pub bg_selected: Color,
     pub fg: Color,
@@ -31,6 +32,7 @@ impl Marquee {
     pub fn new() -> Arc<Self> {
         Arc::new(Marquee {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: Cell::new(Color::rgba(0,0,0,0)),
             bg_selected: BUTTON_BG_SELECTION,
             fg: BUTTON_FOREGROUND,
@@ -100,11 +102,21 @@ impl Text for Marquee {
     }
 }
 
+impl Tooltip for Marquee {}
+
 impl Widget for Marquee {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
 
 
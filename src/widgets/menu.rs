@@ -1,19 +1,23 @@
 use orbclient::{Color, Renderer};
+use orbimage;
 use std::cell::{Cell, RefCell};
 use std::cmp::max;
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, Weak};
 
+use accelerator::Accelerator;
 use cell::{CloneCell, CheckSet};
 use event::Event;
 use point::Point;
 use rect::Rect;
 use theme::{BUTTON_BACKGROUND, BUTTON_BG_SELECTION, BUTTON_FOREGROUND, BUTTON_BORDER,
-            ITEM_BACKGROUND, ITEM_FOREGROUND, ITEM_SELECTION};
-use traits::{Click, Place, Text};
-use widgets::Widget;
+            ITEM_BACKGROUND, ITEM_DISABLED, ITEM_FOREGROUND, ITEM_SELECTION};
+use traits::{Click, Place, Text, Tooltip};
+use widgets::{own_tooltip_at, Overlay, Widget};
 
 pub struct Menu {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Color,
     pub fg: Color,
     text: CloneCell<String>,
@@ -24,23 +28,142 @@ pub struct Menu {
     click_callback: RefCell<Option<Arc<Fn(&Menu, Point)>>>,
     pressed: Cell<bool>,
     activated: Cell<bool>,
+    /// Whether the menu is an entry of another menu, opening to its side
+    nested: Cell<bool>,
+    highlighted: Cell<bool>,
+    current: Cell<Option<usize>>,
+    gutter: Cell<u32>,
+    /// Where the menu was popped up, for a context menu which has no title
+    popup_point: Cell<Option<Point>>,
+    context_targets: RefCell<Vec<Weak<Widget>>>,
+    right_pressed: Cell<bool>,
+    /// The window area, which open menus are kept inside
+    bounds: Cell<Rect>,
     visible: Cell<bool>,
 }
 
 pub struct Separator {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Color,
     pub fg: Color,
 }
 
+/// An entry of a menu
+/// The event method of an entry returns true when the entry was chosen, closing the menu.
 pub trait Entry: Widget {
     fn entry_text(&self) -> String;
+
+    /// Width needed to show the entry
+    fn entry_width(&self) -> u32 {
+        label_width(&self.entry_text())
+    }
+
+    /// Whether the entry can be moved to with the keyboard
+    fn entry_selectable(&self) -> bool {
+        true
+    }
+
+    /// Show the entry as the one moved to, with the mouse or the keyboard
+    fn entry_highlight(&self, _flag: bool) {}
+
+    /// Choose the entry with the keyboard, returning true if the menu should close
+    fn entry_activate(&self) -> bool {
+        false
+    }
+
+    /// Whether a point is over the entry, or over a submenu it opened
+    fn entry_contains(&self, point: Point) -> bool {
+        self.rect().get().contains(point)
+    }
+
+    /// Handle a key sent to the entry while it is the current one
+    /// Returns None if the key is left to the menu, or whether the menu should close.
+    fn entry_key(&self, _event: Event, _redraw: &mut bool) -> Option<bool> {
+        None
+    }
+
+    /// Called when the entry is added to a menu
+    fn entry_added(&self) {}
+
+    /// Width needed left of the text, for a check mark or an icon
+    fn entry_icon_width(&self) -> u32 {
+        0
+    }
+
+    /// Set the width left of the text of every entry of the menu, so their texts line up
+    fn entry_gutter(&self, _width: u32) {}
+
+    /// The radio group of the entry, in which choosing an entry unchecks the others
+    fn entry_group(&self) -> Option<String> {
+        None
+    }
+
+    fn entry_uncheck(&self) {}
+}
+
+/// The letter marked with '&' in a menu text, such as 'f' in "&File"
+/// A literal '&' is written "&&".
+pub fn mnemonic(text: &str) -> Option<char> {
+    let mut marked = false;
+    for c in text.chars() {
+        if c == '&' && !marked {
+            marked = true;
+        } else if marked && c != '&' {
+            return c.to_lowercase().next();
+        } else {
+            marked = false;
+        }
+    }
+    None
+}
+
+/// Width of a menu text, without its mnemonic marker
+pub fn label_width(text: &str) -> u32 {
+    let mut marked = false;
+    let mut count = 0;
+    for c in text.chars() {
+        if c == '&' && !marked {
+            marked = true;
+        } else {
+            marked = false;
+            count += 1;
+        }
+    }
+    count * 8
+}
+
+/// Draw text from an offset inside a rectangle, clipped to it, underlining its mnemonic
+fn draw_text(renderer: &mut Renderer, rect: Rect, offset: Point, text: &str, color: Color) {
+    let mut point = offset;
+    let mut marked = false;
+    for c in text.chars() {
+        if c == '&' && !marked {
+            marked = true;
+            continue;
+        }
+
+        if c == '\n' {
+            point.x = offset.x;
+            point.y += 16;
+        } else {
+            if point.x + 8 <= rect.width as i32 && point.y + 16 <= rect.height as i32 {
+                renderer.char(point.x + rect.x, point.y + rect.y, c, color);
+                if marked && c != '&' {
+                    renderer.rect(point.x + rect.x, point.y + rect.y + 15, 8, 1, color);
+                }
+            }
+            point.x += 8;
+        }
+        marked = false;
+    }
 }
 
 impl Menu {
     pub fn new<S: Into<String>>(name: S) -> Arc<Self> {
         Arc::new(Menu {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: BUTTON_BACKGROUND,
             fg: BUTTON_FOREGROUND,
             text: CloneCell::new(name.into()),
@@ -51,32 +174,248 @@ impl Menu {
             click_callback: RefCell::new(None),
             pressed: Cell::new(false),
             activated: Cell::new(false),
+            nested: Cell::new(false),
+            highlighted: Cell::new(false),
+            current: Cell::new(None),
+            gutter: Cell::new(0),
+            popup_point: Cell::new(None),
+            context_targets: RefCell::new(Vec::new()),
+            right_pressed: Cell::new(false),
+            bounds: Cell::new(Rect::default()),
             visible: Cell::new(true),
         })
     }
 
+    /// Open the menu at a point of the window, as a context menu
+    /// Its title is not shown, and its click callback gets the point it was opened at.
+    pub fn popup(&self, point: Point) {
+        self.popup_point.set(Some(point));
+        self.open(false);
+        self.emit_click(point);
+    }
+
+    /// Pop the menu up when the right button is pressed over a widget
+    pub fn context_for<T: Widget>(&self, widget: &Arc<T>) -> &Self {
+        let widget: Arc<Widget> = widget.clone();
+        self.context_targets.borrow_mut().push(Arc::downgrade(&widget));
+        self
+    }
+
+    fn is_context(&self) -> bool {
+        self.popup_point.get().is_some() || !self.context_targets.borrow().is_empty()
+    }
+
+    /// Adds an entry, which may be another menu opening as a submenu
     pub fn add<T: Entry>(&self, new_entry: &Arc<T>) {
-        let mut rect = self.rect.get();
-        let text_width = new_entry.entry_text().len() as u32 * 8;
-        if rect.width < text_width {
-            rect.width = text_width;
+        new_entry.entry_added();
+        self.entries.borrow_mut().push(new_entry.clone());
+        self.arrange();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.activated.get()
+    }
+
+    /// Open the menu, moving to its first entry when opened with the keyboard
+    pub fn open(&self, key: bool) {
+        self.activated.set(true);
+        self.set_current(None);
+        if key {
+            self.step(1);
         }
+    }
 
-        let mut y = rect.y + rect.height as i32;
-        for entry in self.entries.borrow().iter() {
-            let mut entry_rect = entry.rect().get();
-            y += entry_rect.height as i32;
+    /// Close the menu and its submenus
+    pub fn close(&self) {
+        self.activated.set(false);
+        self.set_current(None);
+        self.popup_point.set(None);
+    }
 
-            if entry_rect.width < rect.width {
-                entry_rect.width = rect.width;
-                entry.rect().set(entry_rect);
-            } else {
-                rect.width = entry_rect.width;
+    /// Height of each entry
+    fn row_height(&self) -> u32 {
+        max(16, self.rect.get().height)
+    }
+
+    /// Place the entries below the menu, to its right when it is a submenu, or at
+    /// the point it was popped up, keeping them inside the window
+    fn arrange(&self) {
+        let rect = self.rect.get();
+        let entries = self.entries.borrow();
+        let row_height = self.row_height();
+
+        let gutter = entries.iter().map(|entry| entry.entry_icon_width()).max().unwrap_or(0);
+        let mut width = if self.nested.get() || self.is_context() { 0 } else { rect.width };
+        for entry in entries.iter() {
+            width = max(width, gutter + entry.entry_width());
+        }
+        let height = entries.len() as i32 * row_height as i32;
+
+        let (mut x, mut y) = if self.nested.get() {
+            (rect.x + rect.width as i32, rect.y)
+        } else if let Some(point) = self.popup_point.get() {
+            (point.x, point.y)
+        } else {
+            (rect.x, rect.y + rect.height as i32)
+        };
+
+        let bounds = self.bounds.get();
+        if bounds.width > 0 && bounds.height > 0 {
+            // A submenu opens to the left when there is no room on the right
+            if x + width as i32 > bounds.width as i32 {
+                x = if self.nested.get() { rect.x - width as i32 } else { bounds.width as i32 - width as i32 };
             }
+            // A context menu opens above the point when there is no room below it
+            if y + height > bounds.height as i32 {
+                y = if self.popup_point.get().is_some() { y - height } else { bounds.height as i32 - height };
+            }
+            x = max(0, x);
+            y = max(0, y);
+        }
+
+        for entry in entries.iter() {
+            entry.rect().set(Rect::new(x, y, width, row_height));
+            entry.entry_gutter(gutter);
+            y += row_height as i32;
+        }
+    }
+
+    /// Uncheck the other entries in the radio group of a chosen entry
+    fn chose(&self, i: usize) {
+        let entries = self.entries.borrow().clone();
+        if let Some(group) = entries.get(i).and_then(|entry| entry.entry_group()) {
+            for (j, entry) in entries.iter().enumerate() {
+                if j != i && entry.entry_group().as_ref() == Some(&group) {
+                    entry.entry_uncheck();
+                }
+            }
+        }
+    }
+
+    /// Whether a point is over the open entries, or their submenus
+    fn open_contains(&self, point: Point) -> bool {
+        self.activated.get() && self.entries.borrow().iter().any(|entry| entry.entry_contains(point))
+    }
+
+    fn set_current(&self, current: Option<usize>) {
+        for (i, entry) in self.entries.borrow().iter().enumerate() {
+            entry.entry_highlight(current == Some(i));
+        }
+        self.current.set(current);
+    }
+
+    /// Move the current entry forward or backward to the next one that can be selected, wrapping around
+    fn step(&self, delta: isize) {
+        let entries = self.entries.borrow().clone();
+        let len = entries.len() as isize;
+        let mut i = match self.current.get() {
+            Some(i) => i as isize,
+            None => if delta > 0 { -1 } else { len },
+        };
+        for _ in 0..len {
+            i = (i + delta + len) % len;
+            if entries[i as usize].entry_selectable() {
+                self.set_current(Some(i as usize));
+                return;
+            }
+        }
+    }
+
+    /// Send a mouse event to the open entries and follow the pointer, returning true if an entry was chosen
+    fn entries_event(&self, event: Event, point: Point, redraw: &mut bool) -> bool {
+        let entries = self.entries.borrow().clone();
+        let mut chosen = false;
+        for (i, entry) in entries.iter().enumerate() {
+            if entry.event(event, false, redraw) {
+                self.chose(i);
+                chosen = true;
+            }
+        }
+
+        // An open submenu stays current while the pointer is over it
+        let mut current = entries.iter().position(|entry| entry.entry_selectable() && entry.rect().get().contains(point));
+        if current.is_none() {
+            current = self.current.get().and_then(|i| {
+                entries.get(i).and_then(|entry| if entry.entry_contains(point) { Some(i) } else { None })
+            });
+        }
+        if current != self.current.get() {
+            self.set_current(current);
+            *redraw = true;
+        }
+
+        chosen
+    }
+
+    /// Handle a key while the menu is open, returning None if the key is not used
+    /// or whether an entry was chosen.
+    fn key(&self, event: Event, redraw: &mut bool) -> Option<bool> {
+        let current = self.current.get().and_then(|i| self.entries.borrow().get(i).cloned());
+        if let Some(ref entry) = current {
+            if let Some(chosen) = entry.entry_key(event, redraw) {
+                return Some(chosen);
+            }
+        }
+
+        let chosen = match event {
+            Event::UpArrow => { self.step(-1); false },
+            Event::DownArrow => { self.step(1); false },
+            Event::Home => { self.set_current(None); self.step(1); false },
+            Event::End => { self.set_current(None); self.step(-1); false },
+            Event::Enter => match (current, self.current.get()) {
+                (Some(entry), Some(i)) => {
+                    let chosen = entry.entry_activate();
+                    if chosen {
+                        self.chose(i);
+                    }
+                    chosen
+                },
+                _ => false,
+            },
+            Event::Escape => { self.close(); false },
+            Event::LeftArrow if self.nested.get() => { self.close(); false },
+            Event::Text { c } => {
+                // Typing the mnemonic of an entry chooses it
+                let c = c.to_lowercase().next();
+                let i = self.entries.borrow().iter().position(|entry| {
+                    entry.entry_selectable() && mnemonic(&entry.entry_text()) == c
+                });
+                match i {
+                    Some(i) => {
+                        self.set_current(Some(i));
+                        let entry = self.entries.borrow()[i].clone();
+                        let chosen = entry.entry_activate();
+                        if chosen {
+                            self.chose(i);
+                        }
+                        chosen
+                    },
+                    None => return None,
+                }
+            },
+            _ => return None,
+        };
+        *redraw = true;
+        Some(chosen)
+    }
+
+    fn draw_entries(&self, renderer: &mut Renderer) {
+        let entries = self.entries.borrow();
+        if let Some(first) = entries.first() {
+            let first = first.rect().get();
+            let last = entries[entries.len() - 1].rect().get();
+            let height = (last.y + last.height as i32 - first.y) as u32;
+            renderer.rect(first.x - 1, first.y - 1, first.width + 2, height + 2, self.fg_border);
+        }
+
+        for entry in entries.iter() {
+            entry.draw(renderer, false);
+        }
+
+        // Open submenus
+        for entry in entries.iter() {
+            entry.draw_overlay(renderer);
         }
-        rect.y = y;
-        new_entry.rect().set(rect);
-        self.entries.borrow_mut().push(new_entry.clone());
     }
 }
 
@@ -107,107 +446,162 @@ impl Click for Menu {
 
 impl Place for Menu {}
 
+impl Tooltip for Menu {}
+
 impl Widget for Menu {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
-    fn draw(&self, renderer: &mut Renderer, _focused: bool) {
-        let rect = self.rect.get();
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
 
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    /// The open entries are above other widgets, so their tooltips come first
+    fn tooltip_at(&self, point: Point) -> Option<String> {
         if self.activated.get() {
-            renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg_pressed);
-        } else {
-            renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+            let entry_tooltip = self.entries.borrow().iter().filter_map(|entry| entry.tooltip_at(point)).next();
+            if entry_tooltip.is_some() {
+                return entry_tooltip;
+            }
         }
+        own_tooltip_at(self, point)
+    }
+
+    fn draw(&self, renderer: &mut Renderer, _focused: bool) {
+        self.arrange();
+        let rect = self.rect.get();
 
-        let text = self.text.borrow();
-        let mut point = self.text_offset.get();
-        for c in text.chars() {
-            if c == '\n' {
-                point.x = self.text_offset.get().x;
-                point.y += 16;
+        if self.is_context() {
+            // Only the entries of a context menu are shown
+        } else if self.nested.get() {
+            // Drawn as an entry of the parent menu, with an arrow to the submenu
+            let (bg, fg) = if self.highlighted.get() || self.activated.get() {
+                (ITEM_SELECTION, ITEM_BACKGROUND)
             } else {
-                if point.x + 8 <= rect.width as i32 && point.y + 16 <= rect.height as i32 {
-                    renderer.char(point.x + rect.x, point.y + rect.y, c, self.fg);
-                }
-                point.x += 8;
+                (ITEM_BACKGROUND, ITEM_FOREGROUND)
+            };
+            renderer.rect(rect.x, rect.y, rect.width, rect.height, bg);
+            let offset = self.text_offset.get() + Point::new(self.gutter.get() as i32, 0);
+            draw_text(renderer, rect, offset, &self.text.get(), fg);
+
+            let center_x = rect.x + rect.width as i32 - 8;
+            let center_y = rect.y + rect.height as i32 / 2;
+            for i in 0..4 {
+                renderer.rect(center_x - 2 + i, center_y - 3 + i, 1, (7 - 2 * i) as u32, fg);
+            }
+        } else {
+            if self.activated.get() {
+                renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg_pressed);
+            } else {
+                renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
             }
-        }
 
-        renderer.rect(rect.x, rect.y + rect.height as i32 - 1, rect.width, 1, self.fg_border);
+            draw_text(renderer, rect, self.text_offset.get(), &self.text.get(), self.fg);
 
+            renderer.rect(rect.x, rect.y + rect.height as i32 - 1, rect.width, 1, self.fg_border);
+        }
+    }
+
+    fn draw_overlay(&self, renderer: &mut Renderer) {
         if self.activated.get() {
-            let mut max_width = 0;
-            let mut max_height = 0;
+            self.bounds.set(Rect::new(0, 0, renderer.width(), renderer.height()));
+            self.arrange();
+            self.draw_entries(renderer);
+        }
+    }
 
-            for entry in self.entries.borrow().iter() {
-                let r = entry.rect().get();
-                max_width = max(max_width, r.x + r.width as i32 - rect.x);
-                max_height = max(max_height, r.y + r.height as i32 - rect.y - rect.height as i32);
-            }
+    fn overlay(&self) -> Option<Overlay> {
+        if self.activated.get() && !self.nested.get() {
+            Some(Overlay::Modal)
+        } else {
+            None
+        }
+    }
 
-            renderer.rect(rect.x - 1, rect.y + rect.height as i32 - 1, max_width as u32 + 2, max_height as u32 + 2, self.fg_border);
+    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
+        self.arrange();
+
+        let point = match event {
+            Event::Mouse { point, .. } => point,
+            _ => {
+                // Keys reach a submenu through entry_key
+                if self.activated.get() && !self.nested.get() {
+                    if self.key(event, redraw) == Some(true) {
+                        self.close();
+                    }
+                }
+                return focused && !self.nested.get();
+            },
+        };
+
+        let rect = self.rect.get();
+        if self.activated.get() && self.entries_event(event, point, redraw) {
+            self.close();
+            *redraw = true;
+            return if self.nested.get() { true } else { focused };
+        }
 
-            for entry in self.entries.borrow().iter() {
-                entry.draw(renderer, _focused);
+        if self.nested.get() {
+            // Hovering the entry opens the submenu, and the parent closes it when another entry is hovered
+            if rect.contains(point) && !self.activated.get() {
+                self.open(false);
+                *redraw = true;
             }
+            return false;
         }
-    }
 
-    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
-        let mut ignore_event = false;
-        if self.activated.get() {
-            for entry in self.entries.borrow().iter() {
-                if entry.event(event, focused, redraw) {
-                    ignore_event = true;
-                    self.pressed.set(true);
+        if self.is_context() {
+            if let Event::Mouse { left_button, right_button, .. } = event {
+                let press = left_button && !self.pressed.get();
+                let right_press = right_button && !self.right_pressed.get();
+                self.pressed.set(left_button);
+                self.right_pressed.set(right_button);
+
+                if (press || right_press) && self.activated.get() && !self.open_contains(point) {
+                    self.close();
+                    *redraw = true;
+                }
+
+                if right_press && !self.activated.get() {
+                    let over_target = self.context_targets.borrow().iter().any(|target| {
+                        target.upgrade().map_or(false, |target| target.rect().get().contains(point))
+                    });
+                    if over_target {
+                        self.popup(point);
+                        *redraw = true;
+                    }
                 }
             }
+            return focused;
         }
 
-        match event {
-            Event::Mouse { point, left_button, .. } => {
-                let mut click = false;
+        if let Event::Mouse { left_button, .. } = event {
+            let press = left_button && !self.pressed.get();
+            self.pressed.set(left_button);
 
-                let rect = self.rect.get();
+            if press {
                 if rect.contains(point) {
-                    if left_button {
-                        self.pressed.set(!self.pressed.get());
-
-                        if self.activated.check_set(true) {
-                            click = true;
-                            *redraw = true;
-                        }
+                    if self.activated.get() {
+                        self.close();
                     } else {
-                        if !self.pressed.get() {
-                            if self.activated.check_set(false) {
-                                click = true;
-                                *redraw = true;
-                            }
-                        }
+                        self.open(false);
                     }
-                } else {
-                    if !ignore_event {
-                        if left_button {
-                            self.pressed.set(false);
-                        } else {
-                            if !self.pressed.get() {
-                                if self.activated.check_set(false) {
-                                    *redraw = true;
-                                }
-                            }
-                        }
-                    }
-                }
+                    *redraw = true;
 
-                if click {
                     let click_point: Point = point - rect.point();
                     self.emit_click(click_point);
+                } else if self.activated.get() && !self.open_contains(point) {
+                    self.close();
+                    *redraw = true;
                 }
             }
-            _ => (),
         }
+
         focused
     }
     
@@ -216,24 +610,272 @@ impl Widget for Menu {
     }
     
     fn name(&self) -> Option<&'static str> {
+        Some("Menu")
+    }
+
+    fn accelerate(&self, accelerator: Accelerator) -> bool {
+        let entries = self.entries.borrow().clone();
+        match entries.iter().position(|entry| entry.accelerate(accelerator)) {
+            Some(i) => {
+                self.chose(i);
+                self.close();
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn accelerators(&self) -> Vec<(Accelerator, String)> {
+        self.entries.borrow().iter().flat_map(|entry| entry.accelerators()).collect()
+    }
+}
+
+impl Entry for Menu {
+    fn entry_text(&self) -> String {
+        self.text.get()
+    }
+
+    /// Room is left for the arrow to the submenu
+    fn entry_width(&self) -> u32 {
+        label_width(&self.text.get()) + 16 + 2 * max(0, self.text_offset.get().x) as u32
+    }
+
+    fn entry_highlight(&self, flag: bool) {
+        self.highlighted.set(flag);
+        if !flag {
+            self.close();
+        }
+    }
+
+    fn entry_activate(&self) -> bool {
+        self.open(true);
+        false
+    }
+
+    fn entry_contains(&self, point: Point) -> bool {
+        self.rect.get().contains(point) || self.open_contains(point)
+    }
+
+    fn entry_key(&self, event: Event, redraw: &mut bool) -> Option<bool> {
         if self.activated.get() {
-            Some("MenuActivated")
-        }else{
-            Some("Menu")
+            return self.key(event, redraw);
+        }
+
+        match event {
+            Event::RightArrow => {
+                self.open(true);
+                *redraw = true;
+                Some(false)
+            },
+            _ => None,
+        }
+    }
+
+    fn entry_added(&self) {
+        self.nested.set(true);
+    }
+
+    fn entry_gutter(&self, width: u32) {
+        self.gutter.set(width);
+    }
+}
+
+/// A row of menus, opened by clicking their titles or with Alt and the letter
+/// marked with '&' in their titles
+/// While a menu is open, hovering another title or pressing Left and Right moves to it.
+pub struct MenuBar {
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    pub fg_border: Color,
+    menus: RefCell<Vec<Arc<Menu>>>,
+    pub visible: Cell<bool>,
+}
+
+impl MenuBar {
+    pub fn new() -> Arc<Self> {
+        Arc::new(MenuBar {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: BUTTON_BACKGROUND,
+            fg_border: BUTTON_BORDER,
+            menus: RefCell::new(Vec::new()),
+            visible: Cell::new(true),
+        })
+    }
+
+    pub fn add(&self, menu: &Arc<Menu>) -> &Self {
+        self.menus.borrow_mut().push(menu.clone());
+        self.arrange();
+        self
+    }
+
+    /// The open menu, if any
+    pub fn open_menu(&self) -> Option<Arc<Menu>> {
+        self.menus.borrow().iter().find(|menu| menu.is_open()).cloned()
+    }
+
+    /// Place the menu titles side by side
+    fn arrange(&self) {
+        let rect = self.rect.get();
+        let mut x = rect.x;
+        for menu in self.menus.borrow().iter() {
+            let width = label_width(&menu.text.get()) + 16;
+            menu.rect.set(Rect::new(x, rect.y, width, rect.height));
+            menu.text_offset.set(Point::new(8, (rect.height as i32 - 16) / 2));
+            x += width as i32;
+        }
+    }
+
+    /// Close the open menu and open another one
+    fn switch(&self, i: usize, key: bool) {
+        let menus = self.menus.borrow().clone();
+        for (j, menu) in menus.iter().enumerate() {
+            if j != i {
+                menu.close();
+            }
+        }
+        if let Some(menu) = menus.get(i) {
+            menu.open(key);
+        }
+    }
+}
+
+impl Place for MenuBar {}
+
+impl Tooltip for MenuBar {}
+
+impl Widget for MenuBar {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn tooltip_at(&self, point: Point) -> Option<String> {
+        if !self.visible.get() {
+            return None;
+        }
+        self.menus.borrow().iter().filter_map(|menu| menu.tooltip_at(point)).next()
+            .or_else(|| own_tooltip_at(self, point))
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        if !self.visible.get() {
+            return;
+        }
+
+        self.arrange();
+        let rect = self.rect.get();
+        renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+        renderer.rect(rect.x, rect.y + rect.height as i32 - 1, rect.width, 1, self.fg_border);
+
+        for menu in self.menus.borrow().iter() {
+            menu.draw(renderer, focused);
         }
-        
     }
 
+    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        self.arrange();
+        let menus = self.menus.borrow().clone();
+        let open = menus.iter().position(|menu| menu.is_open());
+
+        match event {
+            Event::Mouse { point, .. } => {
+                if let Some(i) = open {
+                    let hover = menus.iter().position(|menu| menu.rect.get().contains(point));
+                    if let Some(j) = hover {
+                        if j != i {
+                            self.switch(j, false);
+                            *redraw = true;
+                        }
+                    }
+                }
+
+                for menu in menus.iter() {
+                    menu.event(event, false, redraw);
+                }
+            },
+            Event::Shortcut { c, alt: true, ctrl: false, .. } => {
+                let c = c.to_lowercase().next();
+                if let Some(i) = menus.iter().position(|menu| mnemonic(&menu.text.get()) == c) {
+                    self.switch(i, true);
+                    *redraw = true;
+                }
+            },
+            _ => if let Some(i) = open {
+                match menus[i].key(event, redraw) {
+                    Some(true) => menus[i].close(),
+                    Some(false) => (),
+                    None => match event {
+                        Event::LeftArrow => self.switch((i + menus.len() - 1) % menus.len(), true),
+                        Event::RightArrow => self.switch((i + 1) % menus.len(), true),
+                        _ => (),
+                    },
+                }
+                *redraw = true;
+            },
+        }
+
+        focused
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("MenuBar")
+    }
+
+    fn draw_overlay(&self, renderer: &mut Renderer) {
+        if let Some(menu) = self.open_menu() {
+            menu.draw_overlay(renderer);
+        }
+    }
+
+    fn overlay(&self) -> Option<Overlay> {
+        self.open_menu().and_then(|menu| menu.overlay())
+    }
+
+    fn accelerate(&self, accelerator: Accelerator) -> bool {
+        let menus = self.menus.borrow().clone();
+        menus.iter().any(|menu| menu.accelerate(accelerator))
+    }
+
+    fn accelerators(&self) -> Vec<(Accelerator, String)> {
+        self.menus.borrow().iter().flat_map(|menu| menu.accelerators()).collect()
+    }
 }
 
 pub struct Action {
     rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     bg: Color,
     fg: Color,
     text: CloneCell<String>,
     bg_pressed: Color,
     text_offset: Cell<Point>,
     click_callback: RefCell<Option<Arc<Fn(&Action, Point)>>>,
+    accelerator: Cell<Option<Accelerator>>,
+    /// Whether choosing the action toggles a check mark
+    pub checkable: Cell<bool>,
+    pub checked: Cell<bool>,
+    radio_group: CloneCell<Option<String>>,
+    /// A disabled action is greyed out and can't be chosen
+    pub enabled: Cell<bool>,
+    icon: RefCell<Option<orbimage::Image>>,
+    gutter: Cell<u32>,
     pressed: Cell<bool>,
     hover: Cell<bool>,
 }
@@ -242,16 +884,110 @@ impl Action {
     pub fn new<S: Into<String>>(text: S) -> Arc<Self> {
         Arc::new(Action {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: ITEM_BACKGROUND,
             fg: ITEM_FOREGROUND,
             text: CloneCell::new(text.into()),
             bg_pressed: ITEM_SELECTION,
             text_offset: Cell::new(Point::default()),
             click_callback: RefCell::new(None),
+            accelerator: Cell::new(None),
+            checkable: Cell::new(false),
+            checked: Cell::new(false),
+            radio_group: CloneCell::new(None),
+            enabled: Cell::new(true),
+            icon: RefCell::new(None),
+            gutter: Cell::new(0),
             pressed: Cell::new(false),
             hover: Cell::new(false),
         })
     }
+
+    pub fn checkable(&self, checkable: bool) -> &Self {
+        self.checkable.set(checkable);
+        self
+    }
+
+    pub fn checked(&self, checked: bool) -> &Self {
+        self.checked.set(checked);
+        self
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked.get()
+    }
+
+    /// Make the action one of a group in its menu, of which only one is checked
+    pub fn radio<S: Into<String>>(&self, group: S) -> &Self {
+        self.checkable.set(true);
+        self.radio_group.set(Some(group.into()));
+        self
+    }
+
+    pub fn enabled(&self, enabled: bool) -> &Self {
+        self.enabled.set(enabled);
+        if !enabled {
+            self.hover.set(false);
+            self.pressed.set(false);
+        }
+        self
+    }
+
+    /// Show an image left of the text, scaled down to 16 pixels if it is larger
+    pub fn icon(&self, image: orbimage::Image) -> &Self {
+        let size = max(image.width(), image.height());
+        let image = if size > 16 {
+            let (width, height) = (max(1, image.width() * 16 / size), max(1, image.height() * 16 / size));
+            image.resize(width, height, orbimage::ResizeType::Lanczos3).unwrap_or(image)
+        } else {
+            image
+        };
+        *self.icon.borrow_mut() = Some(image);
+        self
+    }
+
+    pub fn icon_from_path<P: AsRef<Path>>(&self, path: P) -> Result<&Self, String> {
+        Ok(self.icon(orbimage::Image::from_path(path)?))
+    }
+
+    /// Toggle the check mark and run the action
+    fn choose(&self, point: Point) {
+        if self.radio_group.get().is_some() {
+            self.checked.set(true);
+        } else if self.checkable.get() {
+            self.checked.set(!self.checked.get());
+        }
+        self.emit_click(point);
+    }
+
+    fn draw_check(&self, renderer: &mut Renderer, x: i32, y: i32, color: Color) {
+        if self.radio_group.get().is_some() {
+            // A round dot
+            for &(dy, dx) in [(0, 1), (1, 0), (2, 0), (3, 0), (4, 0), (5, 1)].iter() {
+                renderer.rect(x - 3 + dx, y - 3 + dy, 6 - 2 * dx as u32, 1, color);
+            }
+        } else {
+            // A tick, down then up
+            for i in 0..3 {
+                renderer.rect(x - 4 + i, y + i - 1, 1, 2, color);
+            }
+            for i in 0..5 {
+                renderer.rect(x - 1 + i, y + 1 - i, 1, 2, color);
+            }
+        }
+    }
+
+    /// Bind a key combination written like "Ctrl+S" or "F5" to the action
+    /// It is shown next to the text, and runs the action while the menu is closed.
+    pub fn accelerator(&self, text: &str) -> Result<&Self, String> {
+        match Accelerator::parse(text) {
+            Some(accelerator) => {
+                self.accelerator.set(Some(accelerator));
+                Ok(self)
+            },
+            None => Err(format!("Invalid accelerator {}", text)),
+        }
+    }
 }
 
 impl Click for Action {
@@ -279,15 +1015,23 @@ impl Text for Action {
     }
 }
 
+impl Tooltip for Action {}
+
 impl Widget for Action {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         let rect = self.rect.get();
 
-        let (bg, fg) = if self.hover.get() {
+        let (bg, fg) = if !self.enabled.get() {
+            (self.bg, ITEM_DISABLED)
+        } else if self.hover.get() {
             (self.bg_pressed, self.bg)
         } else {
             (self.bg, self.fg)
@@ -295,22 +1039,31 @@ impl Widget for Action {
 
         renderer.rect(rect.x, rect.y, rect.width, rect.height, bg);
 
-        let text = self.text.borrow();
-        let mut point = self.text_offset.get();
-        for c in text.chars() {
-            if c == '\n' {
-                point.x = self.text_offset.get().x;
-                point.y += 16;
-            } else {
-                if point.x + 8 <= rect.width as i32 && point.y + 16 <= rect.height as i32 {
-                    renderer.char(point.x + rect.x, point.y + rect.y, c, fg);
-                }
-                point.x += 8;
-            }
+        // The check mark replaces the icon while the action is checked
+        let gutter = self.gutter.get() as i32;
+        if self.checked.get() {
+            self.draw_check(renderer, rect.x + gutter / 2, rect.y + rect.height as i32 / 2, fg);
+        } else if let Some(ref icon) = *self.icon.borrow() {
+            let x = rect.x + (gutter - icon.width() as i32) / 2;
+            let y = rect.y + (rect.height as i32 - icon.height() as i32) / 2;
+            renderer.image(x, y, icon.width(), icon.height(), icon.data());
+        }
+
+        let offset = self.text_offset.get() + Point::new(gutter, 0);
+        draw_text(renderer, rect, offset, &self.text.get(), fg);
+
+        if let Some(accelerator) = self.accelerator.get() {
+            let text = accelerator.to_string();
+            let offset = Point::new(rect.width as i32 - 8 - label_width(&text) as i32, self.text_offset.get().y);
+            draw_text(renderer, rect, offset, &text, fg);
         }
     }
 
     fn event(&self, event: Event, _focused: bool, redraw: &mut bool) -> bool {
+        if !self.enabled.get() {
+            return false;
+        }
+
         match event {
             Event::Mouse { point, left_button, .. } => {
                 let mut click = false;
@@ -346,7 +1099,8 @@ impl Widget for Action {
 
                 if click {
                     let click_point: Point = point - rect.point();
-                    self.emit_click(click_point);
+                    self.choose(click_point);
+                    return true;
                 }
             }
             _ => (),
@@ -362,29 +1116,93 @@ impl Widget for Action {
     fn name(&self) -> Option<&'static str> {
         Some("Action")
     }
+
+    fn accelerate(&self, accelerator: Accelerator) -> bool {
+        if self.enabled.get() && self.accelerator.get() == Some(accelerator) {
+            self.choose(Point::new(0, 0));
+            return true;
+        }
+        false
+    }
+
+    fn accelerators(&self) -> Vec<(Accelerator, String)> {
+        self.accelerator.get().into_iter().map(|accelerator| (accelerator, self.text.get())).collect()
+    }
 }
 
 impl Entry for Action {
     fn entry_text(&self) -> String {
         self.text.get()
     }
+
+    /// Room is left for the accelerator, right aligned
+    fn entry_width(&self) -> u32 {
+        let width = label_width(&self.text.get()) + 2 * max(0, self.text_offset.get().x) as u32;
+        match self.accelerator.get() {
+            Some(accelerator) => width + 24 + label_width(&accelerator.to_string()),
+            None => width,
+        }
+    }
+
+    fn entry_selectable(&self) -> bool {
+        self.enabled.get()
+    }
+
+    fn entry_highlight(&self, flag: bool) {
+        self.hover.set(flag && self.enabled.get());
+    }
+
+    fn entry_activate(&self) -> bool {
+        if !self.enabled.get() {
+            return false;
+        }
+        self.hover.set(false);
+        self.choose(Point::new(0, 0));
+        true
+    }
+
+    fn entry_icon_width(&self) -> u32 {
+        match *self.icon.borrow() {
+            Some(ref icon) => max(20, icon.width() + 4),
+            None => if self.checkable.get() { 20 } else { 0 },
+        }
+    }
+
+    fn entry_gutter(&self, width: u32) {
+        self.gutter.set(width);
+    }
+
+    fn entry_group(&self) -> Option<String> {
+        self.radio_group.get()
+    }
+
+    fn entry_uncheck(&self) {
+        self.checked.set(false);
+    }
 }
 
 impl Separator {
     pub fn new() -> Arc<Self> {
         Arc::new(Separator {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: ITEM_BACKGROUND,
             fg: ITEM_FOREGROUND,
         })
     }
 }
 
+impl Tooltip for Separator {}
+
 impl Widget for Separator {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         let rect = self.rect.get();
         renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
@@ -393,18 +1211,8 @@ impl Widget for Separator {
         renderer.rect(rect.x, line_y, rect.width, 1, self.fg);
     }
 
-    fn event(&self, event: Event, _focused: bool, _redraw: &mut bool) -> bool {
-        let mut ignore_event = false;
-        match event {
-            Event::Mouse { point, .. } => {
-                let rect = self.rect.get();
-                if rect.contains(point) {
-                    ignore_event = true;
-                }
-            }
-            _ => (),
-        }
-        ignore_event
+    fn event(&self, _event: Event, _focused: bool, _redraw: &mut bool) -> bool {
+        false
     }
     
     fn visible(&self, flag: bool){
@@ -420,4 +1228,53 @@ impl Entry for Separator {
     fn entry_text(&self) -> String {
         String::new()
     }
+
+    fn entry_selectable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use point::Point;
+    use traits::{Place, Tooltip};
+    use widgets::Widget;
+    use super::{label_width, mnemonic, Action, Menu};
+
+    #[test]
+    fn mnemonic_is_the_marked_letter() {
+        assert_eq!(mnemonic("&File"), Some('f'));
+        assert_eq!(mnemonic("Save &As..."), Some('a'));
+        assert_eq!(mnemonic("&Ärger"), Some('ä'));
+        assert_eq!(mnemonic("Edit"), None);
+    }
+
+    #[test]
+    fn mnemonic_skips_literal_ampersands() {
+        assert_eq!(mnemonic("Copy && &Paste"), Some('p'));
+        assert_eq!(mnemonic("A && B"), None);
+        assert_eq!(mnemonic("Trailing &"), None);
+    }
+
+    #[test]
+    fn label_width_leaves_out_markers() {
+        assert_eq!(label_width("&File"), 32);
+        assert_eq!(label_width("A && B"), 40);
+        assert_eq!(label_width(""), 0);
+    }
+
+    #[test]
+    fn open_menus_show_the_tooltips_of_their_actions() {
+        let menu = Menu::new("&File");
+        menu.position(0, 0).size(40, 16);
+        menu.tooltip("File operations");
+        let action = Action::new("&Open");
+        action.tooltip("Open a file");
+        menu.add(&action);
+
+        assert_eq!(menu.tooltip_at(Point::new(4, 4)), Some("File operations".to_string()));
+        assert_eq!(menu.tooltip_at(Point::new(4, 20)), None);
+        menu.open(false);
+        assert_eq!(menu.tooltip_at(Point::new(4, 20)), Some("Open a file".to_string()));
+    }
 }
@@ -2,41 +2,113 @@ use orbclient::Renderer;
 use std::any::Any;
 use std::cell::Cell;
 
+use accelerator::Accelerator;
+use cell::CloneCell;
 use event::Event;
+use point::Point;
 use rect::Rect;
 
 pub use self::button::Button;
+pub use self::code_editor::CodeEditor;
+pub use self::find_bar::FindBar;
 pub use self::grid::Grid;
 pub use self::image::Image;
 pub use self::label::Label;
-pub use self::menu::{ Menu, Action, Separator };
+pub use self::menu::{ Menu, MenuBar, Action, Separator };
 pub use self::progress_bar::ProgressBar;
-pub use self::text_box::TextBox;
+pub use self::scroll_bar::{ScrollBar, Orientation};
+pub use self::scroll_view::ScrollView;
+pub use self::selection::SelectionMode;
+pub use self::table::{Table, Column, CellEditor, SortOrder};
+pub use self::text_box::{TextBox, Search, Span};
+pub use self::tree_view::{TreeView, TreeNode};
 pub use self::list::{ List, Entry };
 pub use self::control_knob::ControlKnob;
-pub use self::toolbar::{Toolbar,ToolbarIcon};
+pub use self::toolbar::{Toolbar, ToolbarIcon, ToolbarMode};
 pub use self::color_swatch::ColorSwatch;
 pub use self::marquee::Marquee;
 
 mod button;
+mod code_editor;
+mod find_bar;
 mod grid;
 mod image;
 mod label;
 mod menu;
 mod progress_bar;
+mod scroll_bar;
+mod scroll_view;
+mod selection;
+mod table;
 mod text_box;
+mod tree_view;
 mod list;
 mod control_knob;
 mod toolbar;
 mod color_swatch;
 mod marquee;
 
+/// How an overlay shown by a widget above all widgets treats events
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Overlay {
+    /// Events only reach the widgets showing overlays, like an open menu
+    Modal,
+    /// Events reach the widget first, then the other widgets, like a tooltip
+    Modeless,
+}
+
 pub trait Widget : Any {
     fn rect(&self) -> &Cell<Rect>;
     fn draw(&self, renderer: &mut Renderer, focused: bool);
     fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool;
     fn visible(&self, flag: bool);
     fn name(&self) -> Option<&'static str>;
+
+    /// Draw the parts of the widget shown above all widgets, such as an open menu
+    fn draw_overlay(&self, _renderer: &mut Renderer) {}
+
+    /// The tooltip text of widgets implementing Tooltip
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        None
+    }
+
+    fn is_visible(&self) -> bool {
+        true
+    }
+
+    /// The tooltip to show for a point over the widget, which containers take from their children
+    fn tooltip_at(&self, point: Point) -> Option<String> {
+        own_tooltip_at(self, point)
+    }
+
+    /// The overlay the widget currently shows, which gets events before the other widgets
+    fn overlay(&self) -> Option<Overlay> {
+        None
+    }
+
+    /// Run the action bound to an accelerator, returning true if there is one
+    fn accelerate(&self, _accelerator: Accelerator) -> bool {
+        false
+    }
+
+    /// Accelerators bound to actions of the widget, with the texts of the actions
+    fn accelerators(&self) -> Vec<(Accelerator, String)> {
+        Vec::new()
+    }
+}
+
+/// The tooltip text of a visible widget, when it is set and the point is over the widget
+/// Containers showing their own tooltip fall back to this after asking their children.
+fn own_tooltip_at<W: Widget + ?Sized>(widget: &W, point: Point) -> Option<String> {
+    let text = match widget.tooltip_text() {
+        Some(tooltip_text) => tooltip_text.get(),
+        None => return None,
+    };
+    if widget.is_visible() && widget.rect().get().contains(point) && !text.is_empty() {
+        Some(text)
+    } else {
+        None
+    }
 }
 
 pub trait ToolbarWidget : Any {
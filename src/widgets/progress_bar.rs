@@ -3,16 +3,17 @@ use std::cell::{Cell, RefCell};
 use std::cmp::{min, max};
 use std::sync::Arc;
 
-use cell::CheckSet;
+use cell::{CheckSet, CloneCell};
 use event::Event;
 use point::Point;
 use rect::Rect;
 use theme::{ITEM_BACKGROUND, ITEM_BORDER, ITEM_SELECTION};
-use traits::{Border, Click, Place};
+use traits::{Border, Click, Place, Tooltip};
 use widgets::Widget;
 
 pub struct ProgressBar {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Cell<Color>,
     pub fg: Cell<Color>,
     pub fg_border: Color,
@@ -30,6 +31,7 @@ impl ProgressBar {
     pub fn new() -> Arc<Self> {
         Arc::new(ProgressBar {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: Cell::new(ITEM_BACKGROUND),
             fg: Cell::new(ITEM_SELECTION),
             fg_border: ITEM_BORDER,
@@ -77,11 +79,21 @@ impl Click for ProgressBar {
 
 impl Place for ProgressBar {}
 
+impl Tooltip for ProgressBar {}
+
 impl Widget for ProgressBar {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         if self.visible.get(){
             let rect = self.rect.get();
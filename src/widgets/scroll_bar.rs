@@ -0,0 +1,405 @@
+use orbclient::{Color, Renderer};
+use std::cell::{Cell, RefCell};
+use std::cmp::{max, min};
+use std::sync::Arc;
+
+use cell::{CheckSet, CloneCell};
+use event::Event;
+use point::Point;
+use rect::Rect;
+use theme::{SCROLL_BAR_BACKGROUND, SCROLL_BAR_FOREGROUND, SCROLL_BAR_SELECTION};
+use traits::{Place, Tooltip};
+use widgets::Widget;
+
+/// Thickness of a scroll bar, as reserved by the widgets embedding one
+pub const SCROLL_BAR_SIZE: u32 = 12;
+
+/// Smallest length of the thumb, so it can still be grabbed for long contents
+const MIN_THUMB: i32 = 16;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// A scroll bar, with arrow buttons at both ends
+/// The value is the offset of the visible page inside the content,
+/// both measured in the same units (usually pixels).
+pub struct ScrollBar {
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    pub fg: Color,
+    pub fg_pressed: Color,
+    pub orientation: Cell<Orientation>,
+    pub value: Cell<i32>,
+    pub content: Cell<u32>,
+    pub page: Cell<u32>,
+    /// Amount scrolled by the arrow buttons
+    pub step: Cell<u32>,
+    pub arrows: Cell<bool>,
+    scroll_callback: RefCell<Option<Arc<Fn(&ScrollBar, i32)>>>,
+    pressed: Cell<bool>,
+    drag: Cell<Option<i32>>,
+    pub visible: Cell<bool>,
+}
+
+impl ScrollBar {
+    pub fn new(orientation: Orientation) -> Arc<Self> {
+        Arc::new(ScrollBar {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: SCROLL_BAR_BACKGROUND,
+            fg: SCROLL_BAR_FOREGROUND,
+            fg_pressed: SCROLL_BAR_SELECTION,
+            orientation: Cell::new(orientation),
+            value: Cell::new(0),
+            content: Cell::new(0),
+            page: Cell::new(0),
+            step: Cell::new(16),
+            arrows: Cell::new(true),
+            scroll_callback: RefCell::new(None),
+            pressed: Cell::new(false),
+            drag: Cell::new(None),
+            visible: Cell::new(true),
+        })
+    }
+
+    /// Set the length of the content and of its visible part
+    pub fn range(&self, content: u32, page: u32) -> &Self {
+        self.content.set(content);
+        self.page.set(page);
+        let value = self.value.get();
+        self.value(value)
+    }
+
+    /// Set the value, clamped to the scrollable range
+    pub fn value(&self, value: i32) -> &Self {
+        self.value.set(max(0, min(self.max_value(), value)));
+        self
+    }
+
+    /// Largest value, reached when the end of the content is visible
+    pub fn max_value(&self) -> i32 {
+        max(0, self.content.get() as i32 - self.page.get() as i32)
+    }
+
+    pub fn step(&self, step: u32) -> &Self {
+        self.step.set(step);
+        self
+    }
+
+    pub fn arrows(&self, arrows: bool) -> &Self {
+        self.arrows.set(arrows);
+        self
+    }
+
+    pub fn on_scroll<T: Fn(&Self, i32) + 'static>(&self, func: T) -> &Self {
+        *self.scroll_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_scroll(&self) {
+        if let Some(ref scroll_callback) = *self.scroll_callback.borrow() {
+            scroll_callback(self, self.value.get());
+        }
+    }
+
+    /// Start and length of the whole bar along the scrolling direction, and its thickness
+    fn extent(&self) -> (i32, i32, i32) {
+        let rect = self.rect.get();
+        match self.orientation.get() {
+            Orientation::Horizontal => (rect.x, rect.width as i32, rect.height as i32),
+            Orientation::Vertical => (rect.y, rect.height as i32, rect.width as i32),
+        }
+    }
+
+    /// Length of each arrow button, which are left out when the bar is too short
+    fn arrow_len(&self) -> i32 {
+        let (_, len, thickness) = self.extent();
+        if self.arrows.get() && len >= 3 * thickness {
+            thickness
+        } else {
+            0
+        }
+    }
+
+    /// Start and length of the track between the arrows, along the scrolling direction
+    fn track(&self) -> (i32, i32) {
+        let (start, len, _) = self.extent();
+        let arrow = self.arrow_len();
+        (start + arrow, len - 2 * arrow)
+    }
+
+    /// Draw an arrow button, pointing backwards or forwards
+    fn draw_arrow(&self, renderer: &mut Renderer, rect: Rect, forward: bool) {
+        renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+        let size = min(rect.width, rect.height) as i32 / 3;
+        let center_x = rect.x + rect.width as i32 / 2;
+        let center_y = rect.y + rect.height as i32 / 2;
+        for i in 0..size {
+            // Rows of the triangle, from its tip
+            let d = if forward { size / 2 - i } else { i - size / 2 };
+            match self.orientation.get() {
+                Orientation::Horizontal => renderer.rect(center_x + d, center_y - i, 1, 2 * i as u32 + 1, self.fg_pressed),
+                Orientation::Vertical => renderer.rect(center_x - i, center_y + d, 2 * i as u32 + 1, 1, self.fg_pressed),
+            }
+        }
+    }
+
+    /// Offset and length of the thumb inside the track
+    fn thumb(&self) -> (i32, i32) {
+        let (_, track) = self.track();
+        let content = max(1, self.content.get() as i32);
+        let len = min(track, max(MIN_THUMB, track * self.page.get() as i32 / content));
+        let max_value = self.max_value();
+        if max_value > 0 {
+            ((track - len) * self.value.get() / max_value, len)
+        } else {
+            (0, len)
+        }
+    }
+
+    fn along(&self, point: Point) -> i32 {
+        match self.orientation.get() {
+            Orientation::Horizontal => point.x,
+            Orientation::Vertical => point.y,
+        }
+    }
+
+    fn scroll_to(&self, value: i32, redraw: &mut bool) {
+        let old = self.value.get();
+        self.value(value);
+        if self.value.get() != old {
+            self.emit_scroll();
+            *redraw = true;
+        }
+    }
+}
+
+impl Place for ScrollBar {}
+
+impl Tooltip for ScrollBar {}
+
+impl Widget for ScrollBar {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, _focused: bool) {
+        if self.visible.get() {
+            let rect = self.rect.get();
+            renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+
+            let fg = if self.drag.get().is_some() {
+                self.fg_pressed
+            } else {
+                self.fg
+            };
+
+            let (start, _) = self.track();
+            let (offset, len) = self.thumb();
+            match self.orientation.get() {
+                Orientation::Horizontal => renderer.rounded_rect(start + offset, rect.y + 2, len as u32, max(rect.height, 4) - 4, 3, true, fg),
+                Orientation::Vertical => renderer.rounded_rect(rect.x + 2, start + offset, max(rect.width, 4) - 4, len as u32, 3, true, fg),
+            }
+
+            let arrow = self.arrow_len();
+            if arrow > 0 {
+                let (back, forward) = match self.orientation.get() {
+                    Orientation::Horizontal => (
+                        Rect::new(rect.x, rect.y, arrow as u32, rect.height),
+                        Rect::new(rect.x + rect.width as i32 - arrow, rect.y, arrow as u32, rect.height)
+                    ),
+                    Orientation::Vertical => (
+                        Rect::new(rect.x, rect.y, rect.width, arrow as u32),
+                        Rect::new(rect.x, rect.y + rect.height as i32 - arrow, rect.width, arrow as u32)
+                    ),
+                };
+                self.draw_arrow(renderer, back, false);
+                self.draw_arrow(renderer, forward, true);
+            }
+        }
+    }
+
+    /// Returns true while the pointer is over the scroll bar or dragging its thumb,
+    /// so embedding widgets know not to handle the event themselves
+    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        match event {
+            Event::Mouse { point, left_button, .. } => {
+                let rect = self.rect.get();
+                let (start, track) = self.track();
+                let (offset, len) = self.thumb();
+                let pos = self.along(point) - start;
+
+                if left_button {
+                    if let Some(grab) = self.drag.get() {
+                        if track > len {
+                            let value = (pos - grab) * self.max_value() / (track - len);
+                            self.scroll_to(value, redraw);
+                        }
+                        return true;
+                    } else if rect.contains(point) {
+                        if self.pressed.check_set(true) {
+                            if pos < 0 {
+                                let value = self.value.get() - self.step.get() as i32;
+                                self.scroll_to(value, redraw);
+                            } else if pos >= track {
+                                let value = self.value.get() + self.step.get() as i32;
+                                self.scroll_to(value, redraw);
+                            } else if pos >= offset && pos < offset + len {
+                                self.drag.set(Some(pos - offset));
+                                *redraw = true;
+                            } else if pos < offset {
+                                let value = self.value.get() - self.page.get() as i32;
+                                self.scroll_to(value, redraw);
+                            } else {
+                                let value = self.value.get() + self.page.get() as i32;
+                                self.scroll_to(value, redraw);
+                            }
+                        }
+                        return true;
+                    }
+                } else {
+                    self.pressed.set(false);
+                    if self.drag.check_set(None) {
+                        *redraw = true;
+                        return true;
+                    }
+                }
+
+                rect.contains(point)
+            }
+            _ => focused,
+        }
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("ScrollBar")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use event::Event;
+    use point::Point;
+    use traits::Place;
+    use widgets::Widget;
+    use super::{Orientation, ScrollBar};
+
+    /// A vertical bar 100 high, with 12 pixel arrows around a 76 pixel track
+    fn scroll_bar() -> Arc<ScrollBar> {
+        let bar = ScrollBar::new(Orientation::Vertical);
+        bar.position(0, 0).size(12, 100);
+        bar.range(400, 100);
+        bar
+    }
+
+    fn click(bar: &ScrollBar, y: i32) {
+        let mut redraw = false;
+        bar.event(Event::Mouse { point: Point::new(6, y), left_button: true, middle_button: false, right_button: false }, false, &mut redraw);
+        bar.event(Event::Mouse { point: Point::new(6, y), left_button: false, middle_button: false, right_button: false }, false, &mut redraw);
+    }
+
+    #[test]
+    fn value_is_clamped_to_the_range() {
+        let bar = scroll_bar();
+        assert_eq!(bar.max_value(), 300);
+        bar.value(500);
+        assert_eq!(bar.value.get(), 300);
+        bar.value(-5);
+        assert_eq!(bar.value.get(), 0);
+
+        bar.value(300);
+        bar.range(400, 350);
+        assert_eq!(bar.value.get(), 50);
+        bar.range(50, 100);
+        assert_eq!(bar.max_value(), 0);
+        assert_eq!(bar.value.get(), 0);
+    }
+
+    #[test]
+    fn thumb_follows_the_value() {
+        let bar = scroll_bar();
+        assert_eq!(bar.track(), (12, 76));
+        assert_eq!(bar.thumb(), (0, 19));
+        bar.value(150);
+        assert_eq!(bar.thumb(), (28, 19));
+        bar.value(300);
+        assert_eq!(bar.thumb(), (57, 19));
+
+        bar.range(100000, 100);
+        assert_eq!(bar.thumb().1, 16);
+        bar.range(50, 100);
+        assert_eq!(bar.thumb(), (0, 76));
+
+        bar.size(12, 30);
+        assert_eq!(bar.track(), (0, 30));
+    }
+
+    #[test]
+    fn clicks_step_and_page() {
+        let bar = scroll_bar();
+        let values = Rc::new(RefCell::new(Vec::new()));
+        let values_clone = values.clone();
+        bar.step(16).on_scroll(move |_, value| values_clone.borrow_mut().push(value));
+
+        // Forward arrow, then the track after the thumb
+        click(&bar, 95);
+        assert_eq!(bar.value.get(), 16);
+        click(&bar, 80);
+        assert_eq!(bar.value.get(), 116);
+
+        // Track before the thumb, then the backward arrow
+        click(&bar, 15);
+        assert_eq!(bar.value.get(), 16);
+        click(&bar, 5);
+        assert_eq!(bar.value.get(), 0);
+
+        // Nothing changes at the start, so nothing is emitted
+        click(&bar, 5);
+        assert_eq!(*values.borrow(), vec![16, 116, 16, 0]);
+    }
+
+    #[test]
+    fn dragging_the_thumb() {
+        let bar = scroll_bar();
+        let mut redraw = false;
+        let mouse = |y, left_button, redraw: &mut bool| bar.event(Event::Mouse { point: Point::new(6, y), left_button: left_button, middle_button: false, right_button: false }, false, redraw);
+
+        // Grab the thumb 5 pixels into it, then drag past the end of the bar
+        assert!(mouse(17, true, &mut redraw));
+        assert_eq!(bar.value.get(), 0);
+        assert!(mouse(36, true, &mut redraw));
+        assert_eq!(bar.value.get(), 100);
+        assert!(mouse(200, true, &mut redraw));
+        assert_eq!(bar.value.get(), 300);
+
+        // Releasing ends the drag, so moving no longer scrolls
+        assert!(mouse(200, false, &mut redraw));
+        assert!(!mouse(150, false, &mut redraw));
+        mouse(17, false, &mut redraw);
+        assert_eq!(bar.value.get(), 300);
+    }
+}
@@ -0,0 +1,482 @@
+use orbclient::{Color, Renderer};
+use orbimage;
+use std::cell::{Cell, RefCell};
+use std::cmp::{max, min};
+use std::sync::Arc;
+
+use cell::{CheckSet, CloneCell};
+use event::Event;
+use point::Point;
+use rect::Rect;
+use theme::WINDOW_BACKGROUND;
+use traits::{Place, Tooltip};
+use widgets::{own_tooltip_at, Orientation, Overlay, ScrollBar, Widget};
+use widgets::scroll_bar::SCROLL_BAR_SIZE;
+
+/// Copy an image to the renderer at a point, clipped to the renderer
+fn blit(renderer: &mut Renderer, image: &orbimage::Image, point: Point) {
+    let x1 = max(0, point.x);
+    let x2 = min(renderer.width() as i32, point.x + image.width() as i32);
+    if x1 >= x2 {
+        return;
+    }
+
+    let src_width = image.width() as usize;
+    let dst_width = renderer.width() as usize;
+    let dst_height = renderer.height() as i32;
+    let src = image.data();
+    let dst = renderer.data_mut();
+    for y in max(0, point.y)..min(dst_height, point.y + image.height() as i32) {
+        let src_row = (y - point.y) as usize * src_width;
+        let dst_row = y as usize * dst_width;
+        dst[dst_row + x1 as usize..dst_row + x2 as usize]
+            .copy_from_slice(&src[src_row + (x1 - point.x) as usize..src_row + (x2 - point.x) as usize]);
+    }
+}
+
+/// A view on a part of a larger widget, scrolled with the mouse wheel, the keyboard
+/// and scroll bars
+/// The content is placed relative to the view, moved by the scroll offset. It is drawn
+/// into an image the size of the view and receives events translated into the view.
+pub struct ScrollView {
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    content: RefCell<Option<Arc<Widget>>>,
+    place_content: RefCell<Option<Box<Fn(i32, i32)>>>,
+    content_focused: Cell<bool>,
+    scroll_offset: Cell<Point>,
+    /// Amount scrolled by the arrow keys, and three times by the mouse wheel
+    pub step: Cell<u32>,
+    v_scroll_bar: Arc<ScrollBar>,
+    h_scroll_bar: Arc<ScrollBar>,
+    image: RefCell<Option<orbimage::Image>>,
+    hover: Cell<bool>,
+    scroll_callback: RefCell<Option<Arc<Fn(&ScrollView, Point)>>>,
+    pub visible: Cell<bool>,
+}
+
+impl ScrollView {
+    pub fn new() -> Arc<Self> {
+        Arc::new(ScrollView {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: WINDOW_BACKGROUND,
+            content: RefCell::new(None),
+            place_content: RefCell::new(None),
+            content_focused: Cell::new(false),
+            scroll_offset: Cell::new(Point::default()),
+            step: Cell::new(16),
+            v_scroll_bar: ScrollBar::new(Orientation::Vertical),
+            h_scroll_bar: ScrollBar::new(Orientation::Horizontal),
+            image: RefCell::new(None),
+            hover: Cell::new(false),
+            scroll_callback: RefCell::new(None),
+            visible: Cell::new(true),
+        })
+    }
+
+    /// Set the scrolled widget, whose size is the size of the scrollable area
+    pub fn content<T: Widget + Place>(&self, widget: &Arc<T>) -> &Self {
+        let place = widget.clone();
+        *self.place_content.borrow_mut() = Some(Box::new(move |x, y| { place.position(x, y); }));
+        *self.content.borrow_mut() = Some(widget.clone());
+        self.arrange();
+        self
+    }
+
+    pub fn step(&self, step: u32) -> &Self {
+        self.step.set(step);
+        self
+    }
+
+    /// Offset of the view inside the content
+    pub fn scroll_position(&self) -> Point {
+        self.scroll_offset.get()
+    }
+
+    /// Scroll the view to an offset inside the content, clamped to the content size
+    pub fn scroll_to(&self, x: i32, y: i32) {
+        let old = self.scroll_offset.get();
+        self.scroll_offset.set(Point::new(x, y));
+        self.arrange();
+        if self.scroll_offset.get() != old {
+            self.emit_scroll();
+        }
+    }
+
+    pub fn on_scroll<T: Fn(&Self, Point) + 'static>(&self, func: T) -> &Self {
+        *self.scroll_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_scroll(&self) {
+        if let Some(ref scroll_callback) = *self.scroll_callback.borrow() {
+            scroll_callback(self, self.scroll_offset.get());
+        }
+    }
+
+    fn content_size(&self) -> (u32, u32) {
+        match *self.content.borrow() {
+            Some(ref content) => {
+                let rect = content.rect().get();
+                (rect.width, rect.height)
+            },
+            None => (0, 0),
+        }
+    }
+
+    /// The visible area, and whether each scroll bar is shown
+    fn view(&self) -> (Rect, bool, bool) {
+        let rect = self.rect.get();
+        let (width, height) = self.content_size();
+
+        let mut v_bar = height > rect.height;
+        let mut h_bar = width > rect.width;
+        // A scroll bar takes space, which may make the other one necessary
+        v_bar |= h_bar && height + SCROLL_BAR_SIZE > rect.height;
+        h_bar |= v_bar && width + SCROLL_BAR_SIZE > rect.width;
+
+        let view = Rect::new(
+            rect.x, rect.y,
+            if v_bar { max(rect.width, SCROLL_BAR_SIZE) - SCROLL_BAR_SIZE } else { rect.width },
+            if h_bar { max(rect.height, SCROLL_BAR_SIZE) - SCROLL_BAR_SIZE } else { rect.height }
+        );
+        (view, v_bar, h_bar)
+    }
+
+    /// Clamp the scroll offset, move the content under the view and place the scroll bars
+    pub fn arrange(&self) {
+        let (view, v_bar, h_bar) = self.view();
+        let (width, height) = self.content_size();
+
+        let mut offset = self.scroll_offset.get();
+        offset.x = max(0, min(width as i32 - view.width as i32, offset.x));
+        offset.y = max(0, min(height as i32 - view.height as i32, offset.y));
+        self.scroll_offset.set(offset);
+
+        self.place_content(Point::default());
+
+        self.v_scroll_bar.visible(v_bar);
+        self.v_scroll_bar.rect.set(Rect::new(view.x + view.width as i32, view.y, SCROLL_BAR_SIZE, view.height));
+        self.v_scroll_bar.range(height, view.height).value(offset.y);
+
+        self.h_scroll_bar.visible(h_bar);
+        self.h_scroll_bar.rect.set(Rect::new(view.x, view.y + view.height as i32, view.width, SCROLL_BAR_SIZE));
+        self.h_scroll_bar.range(width, view.width).value(offset.x);
+    }
+
+    /// Place the content as if the top left corner of the view was at origin
+    fn place_content(&self, origin: Point) {
+        let offset = self.scroll_offset.get();
+        if let Some(ref place_content) = *self.place_content.borrow() {
+            place_content(origin.x - offset.x, origin.y - offset.y);
+        }
+    }
+
+    fn scroll_by(&self, x: i32, y: i32, redraw: &mut bool) {
+        let offset = self.scroll_offset.get();
+        self.scroll_to(offset.x + x, offset.y + y);
+        if self.scroll_offset.get() != offset {
+            *redraw = true;
+        }
+    }
+}
+
+impl Place for ScrollView {
+    fn position(&self, x: i32, y: i32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.x = x;
+        rect.y = y;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+
+    fn size(&self, width: u32, height: u32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.width = width;
+        rect.height = height;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+}
+
+impl Tooltip for ScrollView {}
+
+impl Widget for ScrollView {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        if self.visible.get() {
+            self.arrange();
+            let (view, _, _) = self.view();
+
+            // The content is drawn off screen, then copied to the view
+            if let Some(ref content) = *self.content.borrow() {
+                let mut image = self.image.borrow_mut();
+                let reuse = match *image {
+                    Some(ref image) => image.width() == view.width && image.height() == view.height,
+                    None => false,
+                };
+                if !reuse {
+                    *image = Some(orbimage::Image::new(view.width, view.height));
+                }
+                let image = image.as_mut().unwrap();
+
+                image.set(self.bg);
+                content.draw(image, focused && self.content_focused.get());
+                blit(renderer, image, view.point());
+            } else {
+                renderer.rect(view.x, view.y, view.width, view.height, self.bg);
+            }
+
+            self.v_scroll_bar.draw(renderer, false);
+            self.h_scroll_bar.draw(renderer, false);
+        }
+    }
+
+    fn event(&self, event: Event, mut focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        self.arrange();
+        let (view, _, _) = self.view();
+        let offset = self.scroll_offset.get();
+
+        // An overlay of the content, like an open menu, is not clipped to the view, so
+        // it gets events in window coordinates
+        let content_overlay = self.content.borrow().as_ref().map_or(false, |content| content.overlay().is_some());
+        if content_overlay {
+            self.place_content(view.point());
+        }
+
+        // The content gets the mouse wheel first, so that a list under the mouse scrolls itself
+        let wheel = match event {
+            Event::Scroll { x, y } if self.hover.get() && !content_overlay => Some((x, y)),
+            _ => None,
+        };
+
+        let event = match event {
+            _ if content_overlay => event,
+            Event::Mouse { point, left_button, middle_button, right_button } => {
+                self.hover.set(self.rect.get().contains(point));
+
+                if self.v_scroll_bar.event(event, false, redraw) | self.h_scroll_bar.event(event, false, redraw) {
+                    self.scroll_to(self.h_scroll_bar.value.get(), self.v_scroll_bar.value.get());
+                    if left_button {
+                        focused = true;
+                    }
+                    return focused;
+                }
+
+                if left_button && view.contains(point) {
+                    focused = true;
+                }
+
+                // Hidden parts of the content can't be clicked
+                if !view.contains(point) && (left_button || middle_button || right_button) {
+                    return focused;
+                }
+                Event::Mouse {
+                    point: point - view.point(),
+                    left_button: left_button,
+                    middle_button: middle_button,
+                    right_button: right_button,
+                }
+            },
+            Event::UpArrow | Event::DownArrow | Event::LeftArrow | Event::RightArrow | Event::Home | Event::End
+                if focused && !self.content_focused.get() => {
+                let step = self.step.get() as i32;
+                match event {
+                    Event::UpArrow => self.scroll_by(0, -step, redraw),
+                    Event::DownArrow => self.scroll_by(0, step, redraw),
+                    Event::LeftArrow => self.scroll_by(-step, 0, redraw),
+                    Event::RightArrow => self.scroll_by(step, 0, redraw),
+                    Event::Home => self.scroll_by(-offset.x, -offset.y, redraw),
+                    _ => {
+                        let (_, height) = self.content_size();
+                        self.scroll_by(-offset.x, height as i32, redraw)
+                    },
+                }
+                return focused;
+            },
+            _ => event,
+        };
+
+        let mut content_redraw = false;
+        let content = self.content.borrow().clone();
+        if let Some(content) = content {
+            let content_focused = content.event(event, focused && self.content_focused.get(), &mut content_redraw);
+            if self.content_focused.check_set(content_focused) {
+                *redraw = true;
+            }
+            if content_focused {
+                focused = true;
+            }
+        }
+
+        if content_redraw {
+            *redraw = true;
+        }
+
+        // The view scrolls when nothing under the mouse did, as content that scrolls redraws
+        if let Some((x, y)) = wheel {
+            if !content_redraw {
+                let step = 3 * self.step.get() as i32;
+                self.scroll_by(x * step, -y * step, redraw);
+            }
+        }
+
+        if content_overlay {
+            self.place_content(Point::default());
+        }
+
+        focused
+    }
+
+    fn tooltip_at(&self, point: Point) -> Option<String> {
+        let (view, _, _) = self.view();
+        if self.visible.get() && view.contains(point) {
+            let content_tooltip = self.content.borrow().as_ref().and_then(|content| content.tooltip_at(point - view.point()));
+            if content_tooltip.is_some() {
+                return content_tooltip;
+            }
+        }
+        own_tooltip_at(self, point)
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("ScrollView")
+    }
+
+    fn draw_overlay(&self, renderer: &mut Renderer) {
+        if let Some(ref content) = *self.content.borrow() {
+            let (view, _, _) = self.view();
+            self.place_content(view.point());
+            content.draw_overlay(renderer);
+            self.place_content(Point::default());
+        }
+    }
+
+    fn overlay(&self) -> Option<Overlay> {
+        self.content.borrow().as_ref().and_then(|content| content.overlay())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use event::Event;
+    use point::Point;
+    use traits::{Click, Place};
+    use widgets::{Entry, Label, List, Widget};
+    use super::ScrollView;
+
+    fn scroll_view<T: Widget + Place>(content: &Arc<T>) -> Arc<ScrollView> {
+        let scroll_view = ScrollView::new();
+        scroll_view.position(10, 10).size(100, 100);
+        scroll_view.content(content);
+        scroll_view
+    }
+
+    fn wheel(scroll_view: &ScrollView, y: i32) {
+        let mut redraw = false;
+        scroll_view.event(Event::Mouse { point: Point::new(50, 50), left_button: false, middle_button: false, right_button: false }, false, &mut redraw);
+        scroll_view.event(Event::Scroll { x: 0, y: y }, false, &mut redraw);
+    }
+
+    fn click(scroll_view: &ScrollView, x: i32, y: i32) {
+        let mut redraw = false;
+        scroll_view.event(Event::Mouse { point: Point::new(x, y), left_button: true, middle_button: false, right_button: false }, false, &mut redraw);
+        scroll_view.event(Event::Mouse { point: Point::new(x, y), left_button: false, middle_button: false, right_button: false }, false, &mut redraw);
+    }
+
+    #[test]
+    fn scroll_to_is_clamped_to_the_content() {
+        let label = Label::new();
+        label.size(80, 400);
+        let scroll_view = scroll_view(&label);
+        let offsets = Rc::new(RefCell::new(Vec::new()));
+        let offsets_clone = offsets.clone();
+        scroll_view.on_scroll(move |_, offset| offsets_clone.borrow_mut().push(offset));
+
+        scroll_view.scroll_to(-20, 1000);
+        assert_eq!(scroll_view.scroll_position(), Point::new(0, 300));
+        assert_eq!(scroll_view.v_scroll_bar.value.get(), 300);
+        assert!(!scroll_view.h_scroll_bar.visible.get());
+
+        scroll_view.scroll_to(0, 300);
+        scroll_view.scroll_to(0, 20);
+        assert_eq!(*offsets.borrow(), vec![Point::new(0, 300), Point::new(0, 20)]);
+
+        // Growing the view past the content scrolls back to the start
+        scroll_view.size(100, 500);
+        assert_eq!(scroll_view.scroll_position(), Point::new(0, 0));
+        assert!(!scroll_view.v_scroll_bar.visible.get());
+    }
+
+    #[test]
+    fn clicks_reach_the_content_in_its_coordinates() {
+        let label = Label::new();
+        label.size(80, 400);
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let clicks_clone = clicks.clone();
+        label.on_click(move |_, point| clicks_clone.borrow_mut().push(point));
+        let scroll_view = scroll_view(&label);
+        scroll_view.scroll_to(0, 50);
+
+        click(&scroll_view, 30, 40);
+        assert_eq!(*clicks.borrow(), vec![Point::new(20, 80)]);
+
+        // Below the view the content is hidden, even though it extends there
+        click(&scroll_view, 30, 115);
+        assert_eq!(clicks.borrow().len(), 1);
+    }
+
+    #[test]
+    fn wheel_scrolls_the_view() {
+        let label = Label::new();
+        label.size(100, 400);
+        let scroll_view = scroll_view(&label);
+        wheel(&scroll_view, -1);
+        assert_eq!(scroll_view.scroll_position(), Point::new(0, 48));
+        wheel(&scroll_view, 1);
+        assert_eq!(scroll_view.scroll_position(), Point::new(0, 0));
+    }
+
+    #[test]
+    fn wheel_reaches_scrolling_content_first() {
+        let list = List::new();
+        list.size(88, 400);
+        for _ in 0..40 {
+            list.push(&Entry::new(20));
+        }
+        let scroll_view = scroll_view(&list);
+        wheel(&scroll_view, -1);
+        assert_eq!(scroll_view.scroll_position(), Point::new(0, 0));
+    }
+}
@@ -0,0 +1,253 @@
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::collections::BTreeSet;
+
+/// How entries of a list are selected
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SelectionMode {
+    /// At most one entry is selected
+    Single,
+    /// Clicking an entry toggles its selection
+    Multi,
+    /// Clicking selects one entry, Ctrl+click toggles an entry and Shift+click selects a range
+    Extended,
+}
+
+/// The selection of a list, table or tree view
+/// Items are selected by key, which is their position in lists and trees and their
+/// model row in tables. The current item, moved to with the keyboard or last clicked,
+/// and the anchor of range selections are positions.
+/// Methods changing the selection return whether it changed.
+pub struct Selection {
+    keys: RefCell<BTreeSet<usize>>,
+    pub current: Cell<Option<usize>>,
+    pub anchor: Cell<Option<usize>>,
+    ctrl: Cell<bool>,
+    shift: Cell<bool>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Selection {
+            keys: RefCell::new(BTreeSet::new()),
+            current: Cell::new(None),
+            anchor: Cell::new(None),
+            ctrl: Cell::new(false),
+            shift: Cell::new(false),
+        }
+    }
+
+    pub fn keys(&self) -> Vec<usize> {
+        self.keys.borrow().iter().cloned().collect()
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        self.keys.borrow().contains(&key)
+    }
+
+    pub fn set(&self, keys: BTreeSet<usize>) -> bool {
+        if *self.keys.borrow() != keys {
+            *self.keys.borrow_mut() = keys;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Track the modifier keys, which change what clicks do in extended mode
+    pub fn modifiers(&self, ctrl: bool, shift: bool) {
+        self.ctrl.set(ctrl);
+        self.shift.set(shift);
+    }
+
+    /// Select only the item at position i, whose key is given
+    pub fn select(&self, i: usize, key: usize) -> bool {
+        self.current.set(Some(i));
+        self.anchor.set(Some(i));
+        self.set(Some(key).into_iter().collect())
+    }
+
+    fn toggle(&self, i: usize, key: usize) -> bool {
+        self.anchor.set(Some(i));
+        let mut keys = self.keys.borrow().clone();
+        if !keys.remove(&key) {
+            keys.insert(key);
+        }
+        self.set(keys)
+    }
+
+    /// Select the items from the anchor to position i, adding to the selection if keep is set
+    fn select_range<F: Fn(usize) -> Option<usize>>(&self, i: usize, keep: bool, key: F) -> bool {
+        let anchor = self.anchor.get().unwrap_or(i);
+        let mut keys = if keep { self.keys.borrow().clone() } else { BTreeSet::new() };
+        keys.extend((cmp::min(anchor, i)..cmp::max(anchor, i) + 1).filter_map(key));
+        self.set(keys)
+    }
+
+    /// Select the item at position i that was clicked or moved to, following the selection mode
+    /// Moving with the keyboard in multi mode leaves the selection unchanged.
+    pub fn activate<F: Fn(usize) -> Option<usize>>(&self, mode: SelectionMode, i: usize, keyboard: bool, key: F) -> bool {
+        let k = match key(i) {
+            Some(k) => k,
+            None => return false,
+        };
+        self.current.set(Some(i));
+        match mode {
+            SelectionMode::Single => self.select(i, k),
+            SelectionMode::Multi => !keyboard && self.toggle(i, k),
+            SelectionMode::Extended => if self.shift.get() {
+                self.select_range(i, self.ctrl.get(), key)
+            } else if self.ctrl.get() {
+                !keyboard && self.toggle(i, k)
+            } else {
+                self.select(i, k)
+            },
+        }
+    }
+
+    /// Select the current item, or toggle it unless in single mode, as the space key does
+    pub fn toggle_current<F: Fn(usize) -> Option<usize>>(&self, mode: SelectionMode, key: F) -> bool {
+        let (i, k) = match self.current.get().and_then(|i| key(i).map(|k| (i, k))) {
+            Some(item) => item,
+            None => return false,
+        };
+        if mode == SelectionMode::Single {
+            self.select(i, k)
+        } else {
+            self.toggle(i, k)
+        }
+    }
+
+    /// Keep the current item selected if it is, and unselect the others
+    pub fn single<F: Fn(usize) -> Option<usize>>(&self, key: F) -> bool {
+        let keys = self.current.get().and_then(key).into_iter().filter(|&k| self.contains(k)).collect();
+        self.set(keys)
+    }
+
+    /// Update the current item and the anchor after items moved to other positions
+    pub fn move_positions<F: Fn(usize) -> Option<usize>>(&self, f: F) {
+        self.current.set(self.current.get().and_then(&f));
+        self.anchor.set(self.anchor.get().and_then(&f));
+    }
+
+    /// Update the positions and the keys after items moved, when the keys are positions
+    pub fn remap<F: Fn(usize) -> Option<usize>>(&self, f: F) -> bool {
+        self.move_positions(&f);
+        let keys = self.keys.borrow().iter().filter_map(|&k| f(k)).collect();
+        self.set(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Selection, SelectionMode};
+
+    fn position(i: usize) -> Option<usize> {
+        Some(i)
+    }
+
+    #[test]
+    fn single_replaces_the_selection() {
+        let selection = Selection::new();
+        assert!(selection.activate(SelectionMode::Single, 2, false, position));
+        assert!(selection.activate(SelectionMode::Single, 4, true, position));
+        assert_eq!(selection.keys(), vec![4]);
+        assert_eq!(selection.current.get(), Some(4));
+        assert!(!selection.activate(SelectionMode::Single, 4, false, position));
+    }
+
+    #[test]
+    fn multi_toggles_on_click_only() {
+        let selection = Selection::new();
+        selection.activate(SelectionMode::Multi, 1, false, position);
+        selection.activate(SelectionMode::Multi, 3, false, position);
+        assert!(!selection.activate(SelectionMode::Multi, 5, true, position));
+        assert_eq!(selection.keys(), vec![1, 3]);
+        assert_eq!(selection.current.get(), Some(5));
+
+        selection.activate(SelectionMode::Multi, 1, false, position);
+        assert_eq!(selection.keys(), vec![3]);
+    }
+
+    #[test]
+    fn extended_ranges_from_the_anchor() {
+        let selection = Selection::new();
+        selection.activate(SelectionMode::Extended, 4, false, position);
+        selection.modifiers(false, true);
+        selection.activate(SelectionMode::Extended, 2, false, position);
+        assert_eq!(selection.keys(), vec![2, 3, 4]);
+
+        // The anchor stays, so the range shrinks
+        selection.activate(SelectionMode::Extended, 3, true, position);
+        assert_eq!(selection.keys(), vec![3, 4]);
+
+        // Ctrl+click toggles and moves the anchor, Ctrl+Shift+click adds a range
+        selection.modifiers(true, false);
+        selection.activate(SelectionMode::Extended, 8, false, position);
+        assert_eq!(selection.keys(), vec![3, 4, 8]);
+        selection.modifiers(true, true);
+        selection.activate(SelectionMode::Extended, 10, false, position);
+        assert_eq!(selection.keys(), vec![3, 4, 8, 9, 10]);
+
+        selection.modifiers(false, false);
+        selection.activate(SelectionMode::Extended, 0, false, position);
+        assert_eq!(selection.keys(), vec![0]);
+    }
+
+    #[test]
+    fn ranges_select_keys_of_positions() {
+        // Positions shown in reverse order of the keys, like a sorted table
+        let rows = vec![5, 4, 3, 2, 1, 0];
+        let key = |i: usize| rows.get(i).cloned();
+
+        let selection = Selection::new();
+        selection.activate(SelectionMode::Extended, 1, false, &key);
+        selection.modifiers(false, true);
+        selection.activate(SelectionMode::Extended, 3, false, &key);
+        assert_eq!(selection.keys(), vec![2, 3, 4]);
+        assert_eq!(selection.current.get(), Some(3));
+    }
+
+    #[test]
+    fn toggle_current() {
+        let selection = Selection::new();
+        assert!(!selection.toggle_current(SelectionMode::Multi, position));
+
+        selection.activate(SelectionMode::Multi, 2, true, position);
+        assert!(selection.toggle_current(SelectionMode::Multi, position));
+        assert_eq!(selection.keys(), vec![2]);
+        assert!(selection.toggle_current(SelectionMode::Multi, position));
+        assert_eq!(selection.keys(), Vec::<usize>::new());
+
+        selection.toggle_current(SelectionMode::Single, position);
+        assert!(!selection.toggle_current(SelectionMode::Single, position));
+        assert_eq!(selection.keys(), vec![2]);
+    }
+
+    #[test]
+    fn single_keeps_the_current_item() {
+        let selection = Selection::new();
+        selection.activate(SelectionMode::Multi, 1, false, position);
+        selection.activate(SelectionMode::Multi, 2, false, position);
+        assert!(selection.single(position));
+        assert_eq!(selection.keys(), vec![2]);
+
+        selection.activate(SelectionMode::Multi, 2, false, position);
+        selection.single(position);
+        assert_eq!(selection.keys(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn remap_drops_removed_items() {
+        let selection = Selection::new();
+        selection.activate(SelectionMode::Multi, 1, false, position);
+        selection.activate(SelectionMode::Multi, 3, false, position);
+
+        // Item 1 was removed, moving the items after it up
+        let removed = |i: usize| if i == 1 { None } else if i > 1 { Some(i - 1) } else { Some(i) };
+        assert!(selection.remap(removed));
+        assert_eq!(selection.keys(), vec![2]);
+        assert_eq!(selection.current.get(), Some(2));
+        assert_eq!(selection.anchor.get(), Some(2));
+    }
+}
@@ -0,0 +1,1371 @@
+use orbclient::{Color, Renderer};
+use std::cell::{Cell, RefCell};
+use std::cmp::{self, Ordering};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cell::{CheckSet, CloneCell};
+use event::Event;
+use point::Point;
+use rect::Rect;
+use text_layout::layout_line;
+use theme::{BUTTON_FG_SELECTION, ITEM_BACKGROUND, ITEM_BORDER, ITEM_FOREGROUND, ITEM_HOVER, ITEM_SELECTION,
+            LABEL_BACKGROUND};
+use traits::{Place, Text, Tooltip};
+use widgets::{Orientation, Overlay, ScrollBar, SelectionMode, TextBox, Widget};
+use widgets::list::reuse_image;
+use widgets::selection::Selection;
+use widgets::scroll_bar::SCROLL_BAR_SIZE;
+
+/// Narrowest a column can be resized to
+const MIN_COLUMN_WIDTH: u32 = 16;
+
+/// Distance from a column border at which dragging resizes the column
+const RESIZE_MARGIN: i32 = 3;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// How the cells of a column are edited
+#[derive(Clone, Debug)]
+pub enum CellEditor {
+    /// Free text, in a text box
+    Text,
+    /// One of a list of choices, in a drop down list
+    Choice(Vec<String>),
+    /// A check box, with cells holding "true" or "false"
+    Check,
+}
+
+/// A column of a table
+pub struct Column {
+    pub title: CloneCell<String>,
+    pub width: Cell<u32>,
+    pub sortable: Cell<bool>,
+    /// How cells are edited, or None if they can't be
+    pub editor: RefCell<Option<CellEditor>>,
+}
+
+/// A cell being edited
+enum Editing {
+    Text {
+        row: usize,
+        column: usize,
+        text_box: Arc<TextBox>,
+    },
+    Choice {
+        row: usize,
+        column: usize,
+        choices: Vec<String>,
+        selected: usize,
+        rect: Rect,
+    },
+}
+
+/// Compare cell texts, as numbers if both are numbers
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Draw text in a line, clipped to the given width
+pub fn draw_text(renderer: &mut Renderer, x: i32, y: i32, width: i32, text: &str, color: Color) {
+    for glyph in layout_line(text, 0, text.len(), 1) {
+        if glyph.x + glyph.width > width {
+            break;
+        }
+        for c in text[glyph.start..glyph.end].chars() {
+            renderer.char(x + glyph.x, y, c, color);
+        }
+    }
+}
+
+/// A mouse drag in the header
+#[derive(Copy, Clone, Debug)]
+enum HeaderDrag {
+    /// Pressed on the header of the column at a display position
+    Press(usize, Point),
+    /// Moving the column at a display position
+    Move(usize),
+    /// Resizing a column from its width when the drag started
+    Resize(usize, i32, u32),
+}
+
+/// A table of rows and columns, with the cells given by a model
+/// Only visible cells are requested from the model, so tables can hold many rows.
+/// Rows are identified by their index in the model, whatever the sort order.
+pub struct Table {
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    pub fg: Color,
+    pub bg_header: Color,
+    pub fg_border: Color,
+    pub bg_selection: Color,
+    pub fg_selection: Color,
+    pub bg_hover: Color,
+    columns: RefCell<Vec<Column>>,
+    column_order: RefCell<Vec<usize>>,
+    row_count: Cell<usize>,
+    pub row_height: Cell<u32>,
+    pub header_height: Cell<u32>,
+    cell_text: RefCell<Option<Arc<Fn(&Table, usize, usize) -> String>>>,
+    cell_widget: RefCell<Option<Arc<Fn(&Table, usize, usize) -> Option<Arc<Widget>>>>>,
+    /// Model rows in display order
+    rows: RefCell<Vec<usize>>,
+    /// Display positions of model rows
+    positions: RefCell<Vec<usize>>,
+    sort_column: Cell<Option<(usize, SortOrder)>>,
+    sort_callback: RefCell<Option<Arc<Fn(&Table, usize, SortOrder)>>>,
+    pub selection_mode: Cell<SelectionMode>,
+    selection: Selection,
+    current_column: Cell<usize>,
+    hover: Cell<Option<usize>>,
+    selection_changed_callback: RefCell<Option<Arc<Fn(&Table)>>>,
+    scroll_offset: Cell<Point>,
+    v_scroll_bar: Arc<ScrollBar>,
+    h_scroll_bar: Arc<ScrollBar>,
+    header_drag: Cell<Option<HeaderDrag>>,
+    drop_gap: Cell<Option<usize>>,
+    mouse_over: Cell<bool>,
+    pressed: Cell<bool>,
+    last_press: Cell<Option<(Instant, usize, usize)>>,
+    editing: RefCell<Option<Editing>>,
+    edit_callback: RefCell<Option<Arc<Fn(&Table, usize, usize, &str) -> bool>>>,
+    target: RefCell<Option<::orbimage::Image>>,
+    cell_image: RefCell<Option<::orbimage::Image>>,
+    pub visible: Cell<bool>,
+}
+
+impl Table {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Table {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: ITEM_BACKGROUND,
+            fg: ITEM_FOREGROUND,
+            bg_header: LABEL_BACKGROUND,
+            fg_border: ITEM_BORDER,
+            bg_selection: ITEM_SELECTION,
+            fg_selection: BUTTON_FG_SELECTION,
+            bg_hover: ITEM_HOVER,
+            columns: RefCell::new(Vec::new()),
+            column_order: RefCell::new(Vec::new()),
+            row_count: Cell::new(0),
+            row_height: Cell::new(24),
+            header_height: Cell::new(24),
+            cell_text: RefCell::new(None),
+            cell_widget: RefCell::new(None),
+            rows: RefCell::new(Vec::new()),
+            positions: RefCell::new(Vec::new()),
+            sort_column: Cell::new(None),
+            sort_callback: RefCell::new(None),
+            selection_mode: Cell::new(SelectionMode::Single),
+            selection: Selection::new(),
+            current_column: Cell::new(0),
+            hover: Cell::new(None),
+            selection_changed_callback: RefCell::new(None),
+            scroll_offset: Cell::new(Point::default()),
+            v_scroll_bar: ScrollBar::new(Orientation::Vertical),
+            h_scroll_bar: ScrollBar::new(Orientation::Horizontal),
+            header_drag: Cell::new(None),
+            drop_gap: Cell::new(None),
+            mouse_over: Cell::new(false),
+            pressed: Cell::new(false),
+            last_press: Cell::new(None),
+            editing: RefCell::new(None),
+            edit_callback: RefCell::new(None),
+            target: RefCell::new(None),
+            cell_image: RefCell::new(None),
+            visible: Cell::new(true),
+        })
+    }
+
+    /// Add a column, shown after the others
+    pub fn column<S: Into<String>>(&self, title: S, width: u32) -> &Self {
+        let mut columns = self.columns.borrow_mut();
+        self.column_order.borrow_mut().push(columns.len());
+        columns.push(Column {
+            title: CloneCell::new(title.into()),
+            width: Cell::new(width),
+            sortable: Cell::new(true),
+            editor: RefCell::new(None),
+        });
+        self
+    }
+
+    pub fn columns_len(&self) -> usize {
+        self.columns.borrow().len()
+    }
+
+    pub fn column_width(&self, column: usize, width: u32) -> &Self {
+        if let Some(column) = self.columns.borrow().get(column) {
+            column.width.set(cmp::max(MIN_COLUMN_WIDTH, width));
+        }
+        self
+    }
+
+    pub fn column_sortable(&self, column: usize, sortable: bool) -> &Self {
+        if let Some(column) = self.columns.borrow().get(column) {
+            column.sortable.set(sortable);
+        }
+        self
+    }
+
+    /// Make the cells of a column editable
+    pub fn column_editor(&self, column: usize, editor: CellEditor) -> &Self {
+        if let Some(column) = self.columns.borrow().get(column) {
+            *column.editor.borrow_mut() = Some(editor);
+        }
+        self
+    }
+
+    /// Columns in the order they are shown, which changes when they are dragged
+    pub fn column_order(&self) -> Vec<usize> {
+        self.column_order.borrow().clone()
+    }
+
+    /// Show count rows, with the text of each cell given by the function for a row and column
+    pub fn model<F: Fn(&Table, usize, usize) -> String + 'static>(&self, count: usize, func: F) -> &Self {
+        *self.cell_text.borrow_mut() = Some(Arc::new(func));
+        self.row_count(count);
+        self
+    }
+
+    /// Draw some cells with a widget instead of text, when the function returns one
+    /// The widget is placed over the cell before it is drawn.
+    pub fn cell_widget<F: Fn(&Table, usize, usize) -> Option<Arc<Widget>> + 'static>(&self, func: F) -> &Self {
+        *self.cell_widget.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    /// Change the number of rows of the model
+    pub fn row_count(&self, count: usize) -> &Self {
+        self.row_count.set(count);
+        let selection = self.selection.keys().into_iter().filter(|&row| row < count).collect();
+        if self.selection.set(selection) {
+            self.emit_selection_changed();
+        }
+        self.refresh();
+        self
+    }
+
+    pub fn row_height(&self, height: u32) -> &Self {
+        self.row_height.set(height);
+        self
+    }
+
+    /// Sort the rows again, after the data of the model changed
+    pub fn refresh(&self) {
+        let mut rows: Vec<usize> = (0..self.row_count.get()).collect();
+        if let Some((column, order)) = self.sort_column.get() {
+            let keys: Vec<String> = rows.iter().map(|&row| self.text(row, column)).collect();
+            rows.sort_by(|&a, &b| {
+                let ordering = compare_cells(&keys[a], &keys[b]);
+                if order == SortOrder::Descending { ordering.reverse() } else { ordering }
+            });
+        }
+        let mut positions = vec![0; rows.len()];
+        for (i, &row) in rows.iter().enumerate() {
+            positions[row] = i;
+        }
+        let old_rows = self.rows.replace(rows);
+        *self.positions.borrow_mut() = positions;
+
+        // The current row stays on the same model row
+        self.selection.move_positions(|i| old_rows.get(i).and_then(|&row| self.position(row)));
+        self.hover.set(None);
+        self.arrange();
+    }
+
+    /// Sort the rows by a column
+    pub fn sort(&self, column: usize, order: SortOrder) {
+        if column < self.columns_len() {
+            self.sort_column.set(Some((column, order)));
+            self.refresh();
+            self.emit_sort(column, order);
+        }
+    }
+
+    /// Column and order the rows are sorted by, if any
+    pub fn sort_column(&self) -> Option<(usize, SortOrder)> {
+        self.sort_column.get()
+    }
+
+    pub fn on_sort<T: Fn(&Self, usize, SortOrder) + 'static>(&self, func: T) -> &Self {
+        *self.sort_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_sort(&self, column: usize, order: SortOrder) {
+        let callback = self.sort_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self, column, order);
+        }
+    }
+
+    pub fn selection_mode(&self, mode: SelectionMode) -> &Self {
+        self.selection_mode.set(mode);
+        if mode == SelectionMode::Single {
+            if self.selection.single(|i| self.row(i)) {
+                self.emit_selection_changed();
+            }
+        }
+        self
+    }
+
+    pub fn on_selection_changed<T: Fn(&Self) + 'static>(&self, func: T) -> &Self {
+        *self.selection_changed_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_selection_changed(&self) {
+        let callback = self.selection_changed_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self);
+        }
+    }
+
+    /// The model row moved to with the keyboard or last clicked
+    pub fn selected(&self) -> Option<usize> {
+        self.selection.current.get().and_then(|i| self.row(i))
+    }
+
+    /// The column moved to with the keyboard or last clicked
+    pub fn selected_column(&self) -> usize {
+        self.current_column.get()
+    }
+
+    /// Selected model rows
+    pub fn selected_rows(&self) -> Vec<usize> {
+        self.selection.keys()
+    }
+
+    pub fn is_selected(&self, row: usize) -> bool {
+        self.selection.contains(row)
+    }
+
+    /// Select only the given model row, and scroll it into view
+    pub fn select(&self, row: usize) {
+        if let Some(i) = self.position(row) {
+            self.change_current(i);
+            if self.selection.select(i, row) {
+                self.emit_selection_changed();
+            }
+        }
+    }
+
+    pub fn select_all(&self) {
+        if self.selection_mode.get() != SelectionMode::Single && self.selection.set((0..self.row_count.get()).collect()) {
+            self.emit_selection_changed();
+        }
+    }
+
+    pub fn clear_selection(&self) {
+        if self.selection.set(Default::default()) {
+            self.emit_selection_changed();
+        }
+    }
+
+    /// Called with the new text of an edited cell, to check it and store it in the model
+    /// Returning false rejects the text, leaving the editor open.
+    pub fn on_cell_edit<T: Fn(&Self, usize, usize, &str) -> bool + 'static>(&self, func: T) -> &Self {
+        *self.edit_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_cell_edit(&self, row: usize, column: usize, text: &str) -> bool {
+        let callback = self.edit_callback.borrow().clone();
+        match callback {
+            Some(callback) => callback(self, row, column, text),
+            None => false,
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.borrow().is_some()
+    }
+
+    /// Open the editor of a cell, given by model row and column, if its column is editable
+    /// Check box cells are toggled at once.
+    pub fn edit(&self, row: usize, column: usize) -> bool {
+        self.commit_edit();
+
+        let editor = match self.columns.borrow().get(column) {
+            Some(column) => column.editor.borrow().clone(),
+            None => None,
+        };
+        let i = match self.position(row) {
+            Some(i) => i,
+            None => return false,
+        };
+
+        let editor = match editor {
+            Some(editor) => editor,
+            None => return false,
+        };
+
+        self.change_current(i);
+        self.change_current_column(column);
+        let rect = match self.cell_rect(i, column) {
+            Some(rect) => rect,
+            None => return false,
+        };
+
+        let text = self.text(row, column);
+        match editor {
+            CellEditor::Text => {
+                let text_box = TextBox::new();
+                text_box.text(text);
+                text_box.rect.set(rect);
+                text_box.text_offset(4, (rect.height as i32 - 16) / 2);
+                *self.editing.borrow_mut() = Some(Editing::Text {
+                    row: row,
+                    column: column,
+                    text_box: text_box,
+                });
+            },
+            CellEditor::Choice(choices) => {
+                let selected = choices.iter().position(|choice| *choice == text).unwrap_or(0);
+                *self.editing.borrow_mut() = Some(Editing::Choice {
+                    row: row,
+                    column: column,
+                    rect: self.choices_rect(rect, choices.len()),
+                    choices: choices,
+                    selected: selected,
+                });
+            },
+            CellEditor::Check => {
+                let checked = text == "true";
+                self.emit_cell_edit(row, column, if checked { "false" } else { "true" });
+            },
+        }
+        true
+    }
+
+    /// Store the text of the open editor, closing it unless the text is rejected
+    pub fn commit_edit(&self) -> bool {
+        let edit = match *self.editing.borrow() {
+            Some(Editing::Text { row, column, ref text_box }) => Some((row, column, text_box.text.get())),
+            Some(Editing::Choice { row, column, ref choices, selected, .. }) => Some((row, column, choices[selected].clone())),
+            None => None,
+        };
+
+        match edit {
+            Some((row, column, text)) => {
+                let stored = self.emit_cell_edit(row, column, &text);
+                let choice = match *self.editing.borrow() {
+                    Some(Editing::Choice { .. }) => true,
+                    _ => false,
+                };
+                if stored || choice {
+                    *self.editing.borrow_mut() = None;
+                }
+                stored
+            },
+            None => false,
+        }
+    }
+
+    /// Close the open editor, leaving the cell unchanged
+    pub fn cancel_edit(&self) {
+        *self.editing.borrow_mut() = None;
+    }
+
+    /// Rectangle of a cell in the window, given by displayed row and model column, if it is visible
+    fn cell_rect(&self, i: usize, column: usize) -> Option<Rect> {
+        let position = self.column_order.borrow().iter().position(|&other| other == column)?;
+        let (view, _, _) = self.view();
+        let offset = self.scroll_offset.get();
+        let (x, width) = self.column_span(position);
+        let h = self.row_height.get();
+        let rect = Rect::new(view.x + x - offset.x, view.y + i as i32 * h as i32 - offset.y, width, h);
+        if view.contains_rect(&rect) {
+            Some(rect)
+        } else {
+            None
+        }
+    }
+
+    /// Rectangle of the drop down list of choices for a cell, below it or above it if there is no room
+    fn choices_rect(&self, cell: Rect, count: usize) -> Rect {
+        let rect = self.rect.get();
+        let height = count as u32 * self.row_height.get();
+        let y = if cell.y + cell.height as i32 + height as i32 <= rect.y + rect.height as i32 {
+            cell.y + cell.height as i32
+        } else {
+            cmp::max(rect.y, cell.y - height as i32)
+        };
+        Rect::new(cell.x, y, cell.width, height)
+    }
+
+    /// Handle an event while a cell is edited, returning false if it should be handled by the table
+    fn edit_event(&self, event: Event, redraw: &mut bool) -> bool {
+        let text_box = match *self.editing.borrow() {
+            Some(Editing::Text { ref text_box, .. }) => Some(text_box.clone()),
+            Some(Editing::Choice { .. }) => None,
+            None => return false,
+        };
+
+        match event {
+            Event::Escape => {
+                self.cancel_edit();
+            },
+            Event::Enter => {
+                self.commit_edit();
+            },
+            Event::Scroll { .. } => {
+                self.commit_edit();
+                return false;
+            },
+            Event::Mouse { point, left_button, .. } => match text_box {
+                Some(text_box) => {
+                    if left_button && !text_box.rect.get().contains(point) {
+                        // Clicking elsewhere moves the focus out of the editor
+                        self.commit_edit();
+                        return false;
+                    }
+                    text_box.event(event, true, redraw);
+                },
+                None => {
+                    let pick = match *self.editing.borrow() {
+                        Some(Editing::Choice { rect, .. }) => if rect.contains(point) {
+                            Some(((point.y - rect.y) / cmp::max(1, self.row_height.get()) as i32) as usize)
+                        } else {
+                            None
+                        },
+                        _ => None,
+                    };
+                    if let Some(Editing::Choice { ref mut selected, ref choices, .. }) = *self.editing.borrow_mut() {
+                        if let Some(pick) = pick {
+                            *selected = cmp::min(pick, choices.len() - 1);
+                        }
+                    }
+                    if left_button && !self.pressed.get() {
+                        if pick.is_some() {
+                            self.commit_edit();
+                        } else {
+                            self.cancel_edit();
+                            return false;
+                        }
+                    }
+                    self.pressed.set(left_button);
+                },
+            },
+            _ => match text_box {
+                Some(text_box) => {
+                    text_box.event(event, true, redraw);
+                },
+                None => if let Some(Editing::Choice { ref mut selected, ref choices, .. }) = *self.editing.borrow_mut() {
+                    match event {
+                        Event::UpArrow => if *selected > 0 { *selected -= 1 },
+                        Event::DownArrow => if *selected + 1 < choices.len() { *selected += 1 },
+                        Event::Home => *selected = 0,
+                        Event::End => *selected = choices.len() - 1,
+                        _ => (),
+                    }
+                },
+            },
+        }
+
+        *redraw = true;
+        true
+    }
+
+    /// Draw the open text editor over its cell
+    fn draw_editor(&self, renderer: &mut Renderer) {
+        if let Some(Editing::Text { ref text_box, .. }) = *self.editing.borrow() {
+            text_box.draw(renderer, true);
+        }
+    }
+
+    /// Text of a cell, from the model
+    pub fn text(&self, row: usize, column: usize) -> String {
+        let cell_text = self.cell_text.borrow().clone();
+        match cell_text {
+            Some(cell_text) => cell_text(self, row, column),
+            None => String::new(),
+        }
+    }
+
+    /// Display position of a model row
+    fn position(&self, row: usize) -> Option<usize> {
+        self.positions.borrow().get(row).cloned()
+    }
+
+    /// Model row at a display position
+    fn row(&self, i: usize) -> Option<usize> {
+        self.rows.borrow().get(i).cloned()
+    }
+
+    /// Select a row that was clicked or moved to, following the selection mode
+    fn activate(&self, i: usize, key: bool) {
+        if i < self.row_count.get() {
+            self.change_current(i);
+            if self.selection.activate(self.selection_mode.get(), i, key, |i| self.row(i)) {
+                self.emit_selection_changed();
+            }
+        }
+    }
+
+    /// Move the keyboard focus to a displayed row and scroll it into view
+    fn change_current(&self, i: usize) {
+        if i < self.row_count.get() {
+            self.selection.current.set(Some(i));
+            let (view, _, _) = self.view();
+            let h = self.row_height.get() as i32;
+            let mut offset = self.scroll_offset.get();
+            offset.y = cmp::max(cmp::min(offset.y, i as i32 * h), (i as i32 + 1) * h - view.height as i32);
+            self.scroll_offset.set(offset);
+            self.arrange();
+        }
+    }
+
+    /// Move the keyboard focus to a column and scroll it into view
+    fn change_current_column(&self, column: usize) {
+        let position = match self.column_order.borrow().iter().position(|&other| other == column) {
+            Some(position) => position,
+            None => return,
+        };
+        self.current_column.set(column);
+
+        let (view, _, _) = self.view();
+        let (x, width) = self.column_span(position);
+        let mut offset = self.scroll_offset.get();
+        offset.x = cmp::max(cmp::min(offset.x, x), x + width as i32 - view.width as i32);
+        self.scroll_offset.set(offset);
+        self.arrange();
+    }
+
+    /// Offset and width of the column at a display position, relative to the left of the content
+    fn column_span(&self, position: usize) -> (i32, u32) {
+        let columns = self.columns.borrow();
+        let order = self.column_order.borrow();
+        let x = order.iter().take(position).map(|&column| columns[column].width.get() as i32).sum();
+        (x, order.get(position).map_or(0, |&column| columns[column].width.get()))
+    }
+
+    fn content_size(&self) -> (u32, u32) {
+        let width = self.columns.borrow().iter().map(|column| column.width.get()).sum();
+        (width, self.row_count.get() as u32 * self.row_height.get())
+    }
+
+    /// The area showing rows, below the header, and whether each scroll bar is shown
+    fn view(&self) -> (Rect, bool, bool) {
+        let rect = self.rect.get();
+        let header = cmp::min(rect.height, self.header_height.get());
+        let (width, height) = self.content_size();
+
+        let mut v_bar = height > rect.height - header;
+        let mut h_bar = width > rect.width;
+        v_bar |= h_bar && height + SCROLL_BAR_SIZE > rect.height - header;
+        h_bar |= v_bar && width + SCROLL_BAR_SIZE > rect.width;
+
+        let view = Rect::new(
+            rect.x, rect.y + header as i32,
+            if v_bar { cmp::max(rect.width, SCROLL_BAR_SIZE) - SCROLL_BAR_SIZE } else { rect.width },
+            cmp::max(rect.height - header, if h_bar { SCROLL_BAR_SIZE } else { 0 }) - if h_bar { SCROLL_BAR_SIZE } else { 0 }
+        );
+        (view, v_bar, h_bar)
+    }
+
+    /// Clamp the scroll offset and place the scroll bars
+    fn arrange(&self) {
+        let (view, v_bar, h_bar) = self.view();
+        let (width, height) = self.content_size();
+
+        let mut offset = self.scroll_offset.get();
+        offset.x = cmp::max(0, cmp::min(width as i32 - view.width as i32, offset.x));
+        offset.y = cmp::max(0, cmp::min(height as i32 - view.height as i32, offset.y));
+        self.scroll_offset.set(offset);
+
+        let rect = self.rect.get();
+        self.v_scroll_bar.visible(v_bar);
+        self.v_scroll_bar.rect.set(Rect::new(view.x + view.width as i32, rect.y, SCROLL_BAR_SIZE, (view.y - rect.y) as u32 + view.height));
+        self.v_scroll_bar.range(height, view.height).value(offset.y);
+        self.v_scroll_bar.step(self.row_height.get());
+
+        self.h_scroll_bar.visible(h_bar);
+        self.h_scroll_bar.rect.set(Rect::new(view.x, view.y + view.height as i32, view.width, SCROLL_BAR_SIZE));
+        self.h_scroll_bar.range(width, view.width).value(offset.x);
+    }
+
+    /// Displayed rows overlapping the view
+    fn visible_rows(&self) -> Range<usize> {
+        let (view, _, _) = self.view();
+        let h = cmp::max(1, self.row_height.get()) as i32;
+        let offset = self.scroll_offset.get();
+        let start = offset.y / h;
+        let end = (offset.y + view.height as i32 + h - 1) / h;
+        cmp::min(start as usize, self.row_count.get())..cmp::min(end as usize, self.row_count.get())
+    }
+
+    /// Displayed row at a point in the view
+    fn row_at(&self, point: Point) -> Option<usize> {
+        let (view, _, _) = self.view();
+        if view.contains(point) {
+            let i = ((point.y - view.y + self.scroll_offset.get().y) / cmp::max(1, self.row_height.get()) as i32) as usize;
+            if i < self.row_count.get() {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Display position of the column at a horizontal position in the window
+    fn column_at(&self, x: i32) -> Option<usize> {
+        let (view, _, _) = self.view();
+        let x = x - view.x + self.scroll_offset.get().x;
+        let mut column_x = 0;
+        for position in 0..self.columns_len() {
+            let (_, width) = self.column_span(position);
+            if x >= column_x && x < column_x + width as i32 {
+                return Some(position);
+            }
+            column_x += width as i32;
+        }
+        None
+    }
+
+    /// Display position of a column whose right border is near a horizontal position in the window
+    fn border_at(&self, x: i32) -> Option<usize> {
+        let (view, _, _) = self.view();
+        let x = x - view.x + self.scroll_offset.get().x;
+        (0..self.columns_len()).find(|&position| {
+            let (column_x, width) = self.column_span(position);
+            (x - (column_x + width as i32)).abs() <= RESIZE_MARGIN
+        })
+    }
+
+    /// Index of the gap between columns closest to a horizontal position, where a dragged column is dropped
+    fn drop_gap_at(&self, x: i32) -> usize {
+        match self.column_at(x) {
+            Some(position) => {
+                let (view, _, _) = self.view();
+                let (column_x, width) = self.column_span(position);
+                if x - view.x + self.scroll_offset.get().x - column_x < width as i32 / 2 {
+                    position
+                } else {
+                    position + 1
+                }
+            },
+            None => if x < self.view().0.x { 0 } else { self.columns_len() },
+        }
+    }
+
+    /// Handle a mouse event in the header, or continuing a drag started there
+    fn header_event(&self, point: Point, left_button: bool, redraw: &mut bool) -> bool {
+        let rect = self.rect.get();
+        let (view, _, _) = self.view();
+        let in_header = Rect::new(rect.x, rect.y, view.width, (view.y - rect.y) as u32).contains(point);
+
+        match self.header_drag.get() {
+            Some(HeaderDrag::Resize(position, start_x, start_width)) => {
+                if left_button {
+                    let width = cmp::max(MIN_COLUMN_WIDTH as i32, start_width as i32 + point.x - start_x) as u32;
+                    let column = self.column_order.borrow()[position];
+                    if self.columns.borrow()[column].width.check_set(width) {
+                        self.arrange();
+                        *redraw = true;
+                    }
+                } else {
+                    self.header_drag.set(None);
+                }
+                true
+            },
+            Some(HeaderDrag::Press(position, origin)) => {
+                if left_button {
+                    if (point.x - origin.x).abs() >= 4 {
+                        self.header_drag.set(Some(HeaderDrag::Move(position)));
+                        self.drop_gap.set(Some(self.drop_gap_at(point.x)));
+                        *redraw = true;
+                    }
+                } else {
+                    // A click without moving sorts by the column
+                    self.header_drag.set(None);
+                    let column = self.column_order.borrow()[position];
+                    if self.columns.borrow()[column].sortable.get() {
+                        let order = match self.sort_column.get() {
+                            Some((sorted, SortOrder::Ascending)) if sorted == column => SortOrder::Descending,
+                            _ => SortOrder::Ascending,
+                        };
+                        self.sort(column, order);
+                        *redraw = true;
+                    }
+                }
+                true
+            },
+            Some(HeaderDrag::Move(position)) => {
+                if left_button {
+                    if self.drop_gap.check_set(Some(self.drop_gap_at(point.x))) {
+                        *redraw = true;
+                    }
+                } else {
+                    self.header_drag.set(None);
+                    if let Some(gap) = self.drop_gap.take() {
+                        let mut order = self.column_order.borrow_mut();
+                        let column = order.remove(position);
+                        order.insert(if gap > position { gap - 1 } else { gap }, column);
+                    }
+                    *redraw = true;
+                }
+                true
+            },
+            None => {
+                if in_header && left_button {
+                    if let Some(position) = self.border_at(point.x) {
+                        let (_, width) = self.column_span(position);
+                        self.header_drag.set(Some(HeaderDrag::Resize(position, point.x, width)));
+                    } else if let Some(position) = self.column_at(point.x) {
+                        self.header_drag.set(Some(HeaderDrag::Press(position, point)));
+                    }
+                }
+                in_header
+            },
+        }
+    }
+
+    fn scroll_by(&self, x: i32, y: i32, redraw: &mut bool) {
+        let old = self.scroll_offset.get();
+        self.scroll_offset.set(Point::new(old.x + x, old.y + y));
+        self.arrange();
+        if self.scroll_offset.get() != old {
+            *redraw = true;
+        }
+    }
+
+    /// Draw the column headers
+    fn draw_header(&self, renderer: &mut Renderer, width: u32) {
+        let header = self.header_height.get();
+        let offset = self.scroll_offset.get();
+        renderer.rect(0, 0, width, header, self.bg_header);
+
+        let columns = self.columns.borrow();
+        let mut x = -offset.x;
+        for &column in self.column_order.borrow().iter() {
+            let column = &columns[column];
+            let column_width = column.width.get() as i32;
+            draw_text(renderer, x + 4, (header as i32 - 16) / 2, column_width - 20, &column.title.borrow(), self.fg);
+            x += column_width;
+            renderer.rect(x - 1, 0, 1, header, self.fg_border);
+        }
+
+        // Sort indicator, a small triangle pointing up when ascending
+        if let Some((sorted, order)) = self.sort_column.get() {
+            if let Some(position) = self.column_order.borrow().iter().position(|&column| column == sorted) {
+                let (column_x, column_width) = self.column_span(position);
+                let center_x = column_x - offset.x + column_width as i32 - 10;
+                let center_y = header as i32 / 2;
+                for i in 0..4 {
+                    let y = if order == SortOrder::Ascending { center_y - 2 + i } else { center_y + 1 - i };
+                    renderer.rect(center_x - i, y, 2 * i as u32 + 1, 1, self.fg);
+                }
+            }
+        }
+
+        renderer.rect(0, header as i32 - 1, width, 1, self.fg_border);
+    }
+}
+
+impl Place for Table {
+    fn position(&self, x: i32, y: i32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.x = x;
+        rect.y = y;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+
+    fn size(&self, width: u32, height: u32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.width = width;
+        rect.height = height;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+}
+
+impl Tooltip for Table {}
+
+impl Widget for Table {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        if self.visible.get() {
+            self.arrange();
+            let rect = self.rect.get();
+            let (view, _, _) = self.view();
+            let offset = self.scroll_offset.get();
+            let row_height = self.row_height.get();
+
+            let mut target = self.target.borrow_mut();
+            let target = reuse_image(&mut target, view.width, rect.height);
+            target.set(self.bg);
+
+            self.draw_header(target, view.width);
+
+            let columns = self.columns.borrow();
+            let order = self.column_order.borrow();
+            let cell_widget = self.cell_widget.borrow().clone();
+            let top = view.y - rect.y;
+            for i in self.visible_rows() {
+                let row = self.rows.borrow()[i];
+                let y = top + i as i32 * row_height as i32 - offset.y;
+
+                let selected = self.is_selected(row);
+                if selected {
+                    target.rect(0, y, view.width, row_height, self.bg_selection);
+                } else if self.hover.get() == Some(i) {
+                    target.rect(0, y, view.width, row_height, self.bg_hover);
+                }
+                let fg = if selected { self.fg_selection } else { self.fg };
+
+                let mut x = -offset.x;
+                for &column in order.iter() {
+                    let width = columns[column].width.get();
+                    if x + width as i32 > 0 && x < view.width as i32 {
+                        let widget = cell_widget.as_ref().and_then(|cell_widget| cell_widget(self, row, column));
+                        match widget {
+                            Some(widget) => {
+                                // Widgets are drawn on their own, to clip them to the cell
+                                let mut image = self.cell_image.borrow_mut();
+                                let image = reuse_image(&mut image, width, row_height);
+                                image.set(if selected { self.bg_selection } else { self.bg });
+                                widget.rect().set(Rect::new(0, 0, width, row_height));
+                                widget.draw(image, false);
+                                target.image(x, y, width, row_height, &image.data());
+                            },
+                            None => {
+                                let text = self.text(row, column);
+                                let check = match *columns[column].editor.borrow() {
+                                    Some(CellEditor::Check) => true,
+                                    _ => false,
+                                };
+                                if check {
+                                    let box_y = y + (row_height as i32 - 12) / 2;
+                                    target.rect(x + 4, box_y, 12, 12, fg);
+                                    target.rect(x + 5, box_y + 1, 10, 10, self.bg);
+                                    if text == "true" {
+                                        target.rect(x + 7, box_y + 3, 6, 6, self.bg_selection);
+                                    }
+                                } else {
+                                    draw_text(target, x + 4, y + (row_height as i32 - 16) / 2, width as i32 - 8, &text, fg);
+                                }
+                            },
+                        }
+
+                        if focused && self.selection.current.get() == Some(i) && self.current_column.get() == column {
+                            let color = if selected { self.fg_selection } else { self.bg_selection };
+                            target.rect(x, y, width, 1, color);
+                            target.rect(x, y + row_height as i32 - 1, width, 1, color);
+                            target.rect(x, y, 1, row_height, color);
+                            target.rect(x + width as i32 - 1, y, 1, row_height, color);
+                        }
+                    }
+                    x += width as i32;
+                }
+            }
+
+            // Where a dragged column will be dropped
+            if let Some(gap) = self.drop_gap.get() {
+                let gap_x = if gap < order.len() { self.column_span(gap).0 } else { self.content_size().0 as i32 };
+                target.rect(gap_x - offset.x - 1, 0, 2, rect.height, self.bg_selection);
+            }
+
+            renderer.image(rect.x, rect.y, view.width, rect.height, &target.data());
+
+            self.v_scroll_bar.draw(renderer, false);
+            self.h_scroll_bar.draw(renderer, false);
+
+            self.draw_editor(renderer);
+        }
+    }
+
+    fn event(&self, event: Event, mut focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        self.arrange();
+
+        if focused && self.edit_event(event, redraw) {
+            return focused;
+        }
+
+        match event {
+            Event::Mouse { point, left_button, .. } => {
+                self.mouse_over.set(self.rect.get().contains(point));
+
+                if self.v_scroll_bar.event(event, false, redraw) | self.h_scroll_bar.event(event, false, redraw) {
+                    self.scroll_offset.set(Point::new(self.h_scroll_bar.value.get(), self.v_scroll_bar.value.get()));
+                    self.arrange();
+                    return focused;
+                }
+
+                if self.header_event(point, left_button, redraw) {
+                    self.pressed.set(left_button);
+                    if self.hover.check_set(None) {
+                        *redraw = true;
+                    }
+                    return focused;
+                }
+
+                let i = self.row_at(point);
+                if self.hover.check_set(i) {
+                    *redraw = true;
+                }
+
+                if left_button {
+                    // Only react to the press, not to the mouse moving with the button down
+                    if self.rect.get().contains(point) && self.pressed.check_set(true) {
+                        focused = true;
+                        *redraw = true;
+                        if let Some(i) = i {
+                            if let Some(position) = self.column_at(point.x) {
+                                let column = self.column_order.borrow()[position];
+                                self.current_column.set(column);
+                            }
+                            self.activate(i, false);
+
+                            // Double clicking a cell edits it
+                            let column = self.current_column.get();
+                            let double = match self.last_press.get() {
+                                Some((time, last_i, last_column)) => {
+                                    last_i == i && last_column == column && time.elapsed() < Duration::from_millis(400)
+                                },
+                                None => false,
+                            };
+                            if double {
+                                self.last_press.set(None);
+                                if let Some(row) = self.selected() {
+                                    self.edit(row, column);
+                                }
+                            } else {
+                                self.last_press.set(Some((Instant::now(), i, column)));
+                            }
+                        }
+                    }
+                } else {
+                    self.pressed.set(false);
+                }
+            },
+            Event::Scroll { x, y } if self.mouse_over.get() => {
+                let step = 3 * self.row_height.get() as i32;
+                self.scroll_by(x * step, -y * step, redraw);
+            },
+            Event::Modifiers { ctrl, shift, .. } => {
+                self.selection.modifiers(ctrl, shift);
+            },
+            Event::UpArrow if focused => {
+                match self.selection.current.get() {
+                    Some(i) => if i > 0 { self.activate(i - 1, true) },
+                    None => self.activate(0, true),
+                }
+                *redraw = true;
+            },
+            Event::DownArrow if focused => {
+                match self.selection.current.get() {
+                    Some(i) => if i + 1 < self.row_count.get() { self.activate(i + 1, true) },
+                    None => self.activate(0, true),
+                }
+                *redraw = true;
+            },
+            Event::Home if focused => {
+                self.activate(0, true);
+                *redraw = true;
+            },
+            Event::End if focused => {
+                if self.row_count.get() > 0 {
+                    self.activate(self.row_count.get() - 1, true);
+                    *redraw = true;
+                }
+            },
+            Event::LeftArrow | Event::RightArrow if focused => {
+                let order = self.column_order();
+                if let Some(position) = order.iter().position(|&column| column == self.current_column.get()) {
+                    let position = match event {
+                        Event::LeftArrow => if position > 0 { position - 1 } else { position },
+                        _ => cmp::min(position + 1, order.len() - 1),
+                    };
+                    self.change_current_column(order[position]);
+                    *redraw = true;
+                }
+            },
+            Event::Enter if focused => {
+                if let Some(row) = self.selected() {
+                    self.edit(row, self.current_column.get());
+                    *redraw = true;
+                }
+            },
+            Event::Text { c: ' ' } if focused => {
+                if self.selection.current.get().is_some() {
+                    if self.selection.toggle_current(self.selection_mode.get(), |i| self.row(i)) {
+                        self.emit_selection_changed();
+                    }
+                    *redraw = true;
+                }
+            },
+            Event::Shortcut { c: 'a', ctrl: true, .. } if focused => {
+                self.select_all();
+                *redraw = true;
+            },
+            _ => (),
+        }
+
+        focused
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("Table")
+    }
+
+    /// The drop down list of choices of an edited cell
+    fn draw_overlay(&self, renderer: &mut Renderer) {
+        if let Some(Editing::Choice { ref choices, selected, rect, .. }) = *self.editing.borrow() {
+            let h = self.row_height.get();
+            renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+            for (i, choice) in choices.iter().enumerate() {
+                let y = rect.y + i as i32 * h as i32;
+                let fg = if i == selected {
+                    renderer.rect(rect.x, y, rect.width, h, self.bg_selection);
+                    self.fg_selection
+                } else {
+                    self.fg
+                };
+                draw_text(renderer, rect.x + 4, y + (h as i32 - 16) / 2, rect.width as i32 - 8, choice, fg);
+            }
+            renderer.rect(rect.x, rect.y, rect.width, 1, self.fg_border);
+            renderer.rect(rect.x, rect.y + rect.height as i32 - 1, rect.width, 1, self.fg_border);
+            renderer.rect(rect.x, rect.y, 1, rect.height, self.fg_border);
+            renderer.rect(rect.x + rect.width as i32 - 1, rect.y, 1, rect.height, self.fg_border);
+        }
+    }
+
+    fn overlay(&self) -> Option<Overlay> {
+        match *self.editing.borrow() {
+            Some(Editing::Choice { .. }) => Some(Overlay::Modal),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::cmp::Ordering;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use event::Event;
+    use point::Point;
+    use traits::Place;
+    use widgets::{SelectionMode, Widget};
+    use super::{compare_cells, CellEditor, SortOrder, Table};
+
+    /// A table of fruits and their counts, with rows 24 pixels high below a 24 pixel header
+    fn table() -> Arc<Table> {
+        let cells = vec![("pear", "3"), ("apple", "10"), ("fig", "2")];
+        let table = Table::new();
+        table.size(300, 200);
+        table.column("Name", 100).column("Count", 60);
+        table.model(cells.len(), move |_, row, column| {
+            let (name, count) = cells[row];
+            if column == 0 { name.to_string() } else { count.to_string() }
+        });
+        table
+    }
+
+    fn mouse(table: &Table, x: i32, y: i32, left_button: bool) {
+        let mut redraw = false;
+        table.event(Event::Mouse { point: Point::new(x, y), left_button: left_button, middle_button: false, right_button: false }, true, &mut redraw);
+    }
+
+    /// Record the cell edits of a table, accepting those that are not empty
+    fn record_edits(table: &Table) -> Rc<RefCell<Vec<(usize, usize, String)>>> {
+        let edits = Rc::new(RefCell::new(Vec::new()));
+        let edits_clone = edits.clone();
+        table.on_cell_edit(move |_, row, column, text| {
+            edits_clone.borrow_mut().push((row, column, text.to_string()));
+            !text.is_empty()
+        });
+        edits
+    }
+
+    fn key(table: &Table, event: Event) {
+        let mut redraw = false;
+        table.event(event, true, &mut redraw);
+    }
+
+    fn shown_rows(table: &Table) -> Vec<usize> {
+        (0..3).filter_map(|i| table.row(i)).collect()
+    }
+
+    #[test]
+    fn cells_compare_as_numbers_when_both_are() {
+        assert_eq!(compare_cells("9", "10"), Ordering::Less);
+        assert_eq!(compare_cells(" 2 ", "2.0"), Ordering::Equal);
+        assert_eq!(compare_cells("b", "A"), Ordering::Greater);
+        assert_eq!(compare_cells("10", "9a"), Ordering::Less);
+    }
+
+    #[test]
+    fn sorting_orders_rows_and_their_positions() {
+        let table = table();
+        assert_eq!(shown_rows(&table), vec![0, 1, 2]);
+
+        table.sort(1, SortOrder::Ascending);
+        assert_eq!(shown_rows(&table), vec![2, 0, 1]);
+        assert_eq!((0..3).filter_map(|row| table.position(row)).collect::<Vec<usize>>(), vec![1, 2, 0]);
+
+        table.sort(0, SortOrder::Descending);
+        assert_eq!(shown_rows(&table), vec![0, 2, 1]);
+        assert_eq!(table.sort_column(), Some((0, SortOrder::Descending)));
+    }
+
+    #[test]
+    fn selection_stays_on_model_rows_when_sorting() {
+        let table = table();
+        table.selection_mode(SelectionMode::Multi);
+        table.select(1);
+        table.sort(1, SortOrder::Ascending);
+        assert_eq!(table.selected(), Some(1));
+        assert_eq!(table.selection.current.get(), Some(2));
+        assert_eq!(table.selected_rows(), vec![1]);
+
+        // Moving down from apple, now last, stays on it
+        let mut redraw = false;
+        table.event(Event::DownArrow, true, &mut redraw);
+        assert_eq!(table.selected(), Some(1));
+        table.event(Event::UpArrow, true, &mut redraw);
+        assert_eq!(table.selected(), Some(0));
+    }
+
+    #[test]
+    fn clicking_a_header_sorts_by_its_column() {
+        let table = table();
+        mouse(&table, 20, 10, true);
+        mouse(&table, 20, 10, false);
+        assert_eq!(table.sort_column(), Some((0, SortOrder::Ascending)));
+        mouse(&table, 20, 10, true);
+        mouse(&table, 20, 10, false);
+        assert_eq!(table.sort_column(), Some((0, SortOrder::Descending)));
+
+        table.column_sortable(1, false);
+        mouse(&table, 120, 10, true);
+        mouse(&table, 120, 10, false);
+        assert_eq!(table.sort_column(), Some((0, SortOrder::Descending)));
+    }
+
+    #[test]
+    fn dragging_a_header_border_resizes_the_column() {
+        let table = table();
+        mouse(&table, 101, 10, true);
+        mouse(&table, 131, 10, true);
+        mouse(&table, 131, 10, false);
+        assert_eq!(table.columns.borrow()[0].width.get(), 130);
+
+        // Columns don't get narrower than the minimum
+        mouse(&table, 130, 10, true);
+        mouse(&table, -100, 10, true);
+        mouse(&table, -100, 10, false);
+        assert_eq!(table.columns.borrow()[0].width.get(), 16);
+        assert_eq!(table.sort_column(), None);
+    }
+
+    #[test]
+    fn dragging_a_header_moves_the_column() {
+        let table = table();
+        mouse(&table, 20, 10, true);
+        mouse(&table, 150, 10, true);
+        assert_eq!(table.drop_gap.get(), Some(2));
+        mouse(&table, 150, 10, false);
+        assert_eq!(table.column_order(), vec![1, 0]);
+        assert_eq!(table.sort_column(), None);
+
+        // Clicks find columns in their new places
+        assert_eq!(table.column_at(20), Some(0));
+        mouse(&table, 20, 30, true);
+        assert_eq!(table.selected_column(), 1);
+    }
+
+    #[test]
+    fn enter_commits_the_edited_text() {
+        let table = table();
+        let edits = record_edits(&table);
+        assert!(!table.edit(1, 0));
+
+        table.column_editor(0, CellEditor::Text);
+        assert!(table.edit(1, 0));
+        key(&table, Event::Text { c: 's' });
+        key(&table, Event::Enter);
+        assert!(!table.is_editing());
+        assert_eq!(*edits.borrow(), vec![(1, 0, "apples".to_string())]);
+    }
+
+    #[test]
+    fn rejected_text_keeps_the_editor_open() {
+        let table = table();
+        let edits = record_edits(&table);
+        table.column_editor(1, CellEditor::Text);
+        table.edit(2, 1);
+        key(&table, Event::Backspace);
+        assert!(!table.commit_edit());
+        assert!(table.is_editing());
+        assert_eq!(*edits.borrow(), vec![(2, 1, String::new())]);
+    }
+
+    #[test]
+    fn escape_cancels_the_edit() {
+        let table = table();
+        let edits = record_edits(&table);
+        table.column_editor(0, CellEditor::Text);
+        table.edit(0, 0);
+        key(&table, Event::Text { c: 's' });
+        key(&table, Event::Escape);
+        assert!(!table.is_editing());
+        assert!(edits.borrow().is_empty());
+    }
+
+    #[test]
+    fn choices_and_check_boxes() {
+        let table = table();
+        let edits = record_edits(&table);
+        table.column_editor(1, CellEditor::Choice(vec!["2".to_string(), "3".to_string(), "10".to_string()]));
+        table.edit(0, 1);
+        key(&table, Event::DownArrow);
+        key(&table, Event::Enter);
+        assert!(!table.is_editing());
+
+        // Check boxes toggle at once, without an editor
+        table.column_editor(0, CellEditor::Check);
+        table.edit(2, 0);
+        assert!(!table.is_editing());
+        assert_eq!(*edits.borrow(), vec![(0, 1, "10".to_string()), (2, 0, "true".to_string())]);
+    }
+}
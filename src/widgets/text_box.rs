@@ -3,29 +3,223 @@ use std::cell::{Cell, RefCell};
 use std::cmp::{max, min};
 use std::ops::Deref;
 use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
 
 use cell::{CloneCell, CheckSet};
 use event::Event;
 use point::Point;
 use rect::Rect;
-use theme::{TEXT_BACKGROUND, TEXT_BORDER, TEXT_FOREGROUND, TEXT_SELECTION};
-use traits::{Border, Click, Enter, EventFilter, Place, Text};
-use widgets::Widget;
-
-/// Find next character index
-fn next_i(text: &str, text_i: usize) -> usize {
-    let slice = &text[text_i..];
-    slice.char_indices().skip(1).next().unwrap_or((slice.len(), '\0')).0 + text_i
+use text_layout::{Glyph, grapheme_width, is_rtl, layout_line, next_grapheme, prev_grapheme, text_width};
+use theme::{TEXT_BACKGROUND, TEXT_BORDER, TEXT_FOREGROUND, TEXT_SELECTION, TEXT_GUTTER_BACKGROUND, TEXT_GUTTER_FOREGROUND,
+            TEXT_MATCH, TEXT_MATCH_CURRENT};
+use traits::{Border, Click, Enter, EventFilter, Place, Text, Tooltip};
+use widgets::{Orientation, ScrollBar, Widget};
+use widgets::scroll_bar::SCROLL_BAR_SIZE;
+
+/// A visual line of text
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    /// Byte index of the first character
+    start: usize,
+    /// Byte index after the last character, not counting the newline
+    end: usize,
+    /// Index of the logical line, counted in newlines
+    number: usize,
+    /// Whether this visual line starts a logical line
+    first: bool,
+    /// Whether the logical line reads right to left
+    rtl: bool,
 }
 
-/// Find last character index
-fn prev_i(text: &str, text_i: usize) -> usize {
-    let slice = &text[.. text_i];
-    slice.char_indices().rev().next().unwrap_or((0, '\0')).0
+/// Split text into visual lines, breaking after whitespace if a wrap width is given
+fn wrap_lines(text: &str, wrap_width: Option<i32>, tab_width: u32) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (number, line) in text.split('\n').enumerate() {
+        let end = start + line.len();
+        let rtl = is_rtl(line);
+        let mut line_start = start;
+        let mut first = true;
+
+        if let Some(wrap_width) = wrap_width {
+            let mut x = 0;
+            let mut break_i = None;
+            for (i, g) in line.grapheme_indices(true) {
+                let i = start + i;
+                let w = grapheme_width(g, tab_width);
+                if x + w > wrap_width && i > line_start {
+                    let split = match break_i {
+                        Some(break_i) if break_i > line_start => break_i,
+                        _ => i,
+                    };
+                    lines.push(Line { start: line_start, end: split, number: number, first: first, rtl: rtl });
+                    x = text_width(&text[split..i], tab_width);
+                    line_start = split;
+                    first = false;
+                    break_i = None;
+                }
+                x += w;
+                if g.chars().all(char::is_whitespace) {
+                    break_i = Some(i + g.len());
+                }
+            }
+        }
+
+        lines.push(Line { start: line_start, end: end, number: number, first: first, rtl: rtl });
+        start = end + 1;
+    }
+    lines
+}
+
+/// Glyphs of a visual line in display order, with byte indexes into text
+fn line_glyphs(text: &str, line: &Line, tab_width: u32) -> Vec<Glyph> {
+    let paragraph_start = text[..line.start].rfind('\n').map_or(0, |i| i + 1);
+    let paragraph_end = text[line.end..].find('\n').map_or(text.len(), |i| line.end + i);
+    let mut glyphs = layout_line(&text[paragraph_start..paragraph_end], line.start - paragraph_start, line.end - paragraph_start, tab_width);
+    for glyph in glyphs.iter_mut() {
+        glyph.start += paragraph_start;
+        glyph.end += paragraph_start;
+    }
+    glyphs
+}
+
+/// Offset of a visual line in the view, as right to left lines are aligned right
+/// Room for the caret is left at the end of the line.
+fn line_x(line: &Line, glyphs: &[Glyph], view_width: i32) -> i32 {
+    if line.rtl {
+        let width = glyphs.last().map_or(0, |glyph| glyph.x + glyph.width);
+        max(8, view_width - width)
+    } else {
+        0
+    }
+}
+
+/// Find the visual line holding a character index
+fn line_of(lines: &[Line], text_i: usize) -> usize {
+    let mut row = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if line.start > text_i {
+            break;
+        }
+        row = i;
+    }
+    row
+}
+
+/// A range of text drawn with its own colors
+/// Spans given later take precedence over earlier ones.
+#[derive(Copy, Clone, Debug)]
+pub struct Span {
+    /// Byte index of the first character
+    pub start: usize,
+    /// Byte index after the last character
+    pub end: usize,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, fg: Option<Color>, bg: Option<Color>) -> Self {
+        Span {
+            start: start,
+            end: end,
+            fg: fg,
+            bg: bg,
+        }
+    }
+}
+
+/// Text to search for in a text box
+#[derive(Clone, Debug, Default)]
+pub struct Search {
+    pub pattern: String,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+impl Search {
+    pub fn new<S: Into<String>>(pattern: S) -> Self {
+        Search {
+            pattern: pattern.into(),
+            case_sensitive: false,
+            whole_word: false,
+        }
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// Length of the match starting at byte index i, if there is one
+    fn match_at(&self, text: &str, i: usize) -> Option<usize> {
+        let mut chars = text[i..].chars();
+        let mut len = 0;
+        for p in self.pattern.chars() {
+            let c = chars.next()?;
+            let equal = if self.case_sensitive {
+                c == p
+            } else {
+                c == p || c.to_lowercase().eq(p.to_lowercase())
+            };
+            if !equal {
+                return None;
+            }
+            len += c.len_utf8();
+        }
+
+        if self.whole_word {
+            let is_word = |c: char| c.is_alphanumeric() || c == '_';
+            if text[..i].chars().rev().next().map_or(false, &is_word) || text[i + len..].chars().next().map_or(false, &is_word) {
+                return None;
+            }
+        }
+
+        Some(len)
+    }
+
+    /// Byte ranges of all matches in text, not overlapping
+    pub fn find_all(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut matches = Vec::new();
+        if self.pattern.is_empty() {
+            return matches;
+        }
+
+        let mut next = 0;
+        for (i, _) in text.char_indices() {
+            if i >= next {
+                if let Some(len) = self.match_at(text, i) {
+                    matches.push((i, i + len));
+                    next = i + len;
+                }
+            }
+        }
+        matches
+    }
+}
+
+/// Text laid out inside a text box
+struct Layout {
+    lines: Vec<Line>,
+    /// Area the text is drawn in, in absolute coordinates
+    view: Rect,
+    /// Width of the line number gutter
+    gutter: u32,
+    /// Size of the laid out text
+    width: u32,
+    height: u32,
+    v_bar: bool,
+    h_bar: bool,
 }
 
 pub struct TextBox {
     pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
     pub bg: Color,
     pub fg: Color,
     pub fg_border: Color,
@@ -49,6 +243,25 @@ pub struct TextBox {
     /// or should return the event it received if it wants the default
     /// handler deal with it.
     pub event_filter: RefCell<Option<Arc<Fn(&TextBox, Event, &mut bool, &mut bool) -> Option<Event>>>>,
+    /// In multi-line mode, Enter always inserts a newline and scroll bars
+    /// are shown when the text does not fit
+    pub multi_line: Cell<bool>,
+    pub word_wrap: Cell<bool>,
+    pub line_numbers: Cell<bool>,
+    pub auto_scroll: Cell<bool>,
+    /// Number of character cells a tab spans
+    pub tab_width: Cell<u32>,
+    /// Colored ranges of text, such as syntax highlighting
+    pub spans: RefCell<Vec<Span>>,
+    pub search: CloneCell<Option<Search>>,
+    /// Text being composed by an input method, not yet part of the text
+    pub preedit: CloneCell<String>,
+    pub bg_match: Color,
+    pub bg_match_current: Color,
+    pub bg_gutter: Color,
+    pub fg_gutter: Color,
+    v_scroll_bar: Arc<ScrollBar>,
+    h_scroll_bar: Arc<ScrollBar>,
     pressed: Cell<bool>,
     pub visible: Cell<bool>,
 }
@@ -57,6 +270,7 @@ impl TextBox {
     pub fn new() -> Arc<Self> {
         Arc::new(TextBox {
             rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
             bg: TEXT_BACKGROUND,
             fg: TEXT_FOREGROUND,
             fg_border: TEXT_BORDER,
@@ -72,6 +286,20 @@ impl TextBox {
             click_callback: RefCell::new(None),
             enter_callback: RefCell::new(None),
             event_filter: RefCell::new(None),
+            multi_line: Cell::new(false),
+            word_wrap: Cell::new(false),
+            line_numbers: Cell::new(false),
+            auto_scroll: Cell::new(true),
+            tab_width: Cell::new(4),
+            spans: RefCell::new(Vec::new()),
+            search: CloneCell::new(None),
+            preedit: CloneCell::new(String::new()),
+            bg_match: TEXT_MATCH,
+            bg_match_current: TEXT_MATCH_CURRENT,
+            bg_gutter: TEXT_GUTTER_BACKGROUND,
+            fg_gutter: TEXT_GUTTER_FOREGROUND,
+            v_scroll_bar: ScrollBar::new(Orientation::Vertical),
+            h_scroll_bar: ScrollBar::new(Orientation::Horizontal),
             pressed: Cell::new(false),
             visible: Cell::new(true),
         })
@@ -86,6 +314,275 @@ impl TextBox {
         self.mask_char.set(mask_char);
         self
     }
+
+    pub fn multi_line(&self, multi_line: bool) -> &Self {
+        self.multi_line.set(multi_line);
+        self
+    }
+
+    /// Break lines at whitespace to fit the width, in multi-line mode
+    pub fn word_wrap(&self, word_wrap: bool) -> &Self {
+        self.word_wrap.set(word_wrap);
+        self
+    }
+
+    /// Show line numbers on the left, in multi-line mode
+    pub fn line_numbers(&self, line_numbers: bool) -> &Self {
+        self.line_numbers.set(line_numbers);
+        self
+    }
+
+    /// Keep the caret in view when it moves
+    pub fn auto_scroll(&self, auto_scroll: bool) -> &Self {
+        self.auto_scroll.set(auto_scroll);
+        self
+    }
+
+    pub fn tab_width(&self, tab_width: u32) -> &Self {
+        self.tab_width.set(tab_width);
+        self
+    }
+
+    /// Insert text at the caret, and move the caret after it
+    pub fn insert(&self, text: &str) {
+        let text_i = self.text_i.get();
+        self.replace_range(text_i, text_i, text);
+    }
+
+    /// Replace a byte range of the text, and move the caret after the replacement
+    pub fn replace_range(&self, start: usize, end: usize, text: &str) {
+        {
+            let mut self_text = self.text.borrow_mut();
+            self_text.drain(start..end);
+            self_text.insert_str(start, text);
+            self.text_i.set(start + text.len());
+        }
+
+        if self.auto_scroll.get() {
+            self.scroll_to_caret();
+        } else {
+            let text = self.text.borrow();
+            self.update_scroll_bars(&self.layout(&text));
+        }
+    }
+
+    /// Set the text to search for, highlighting all of its matches
+    pub fn search(&self, search: Option<Search>) -> &Self {
+        self.search.set(search);
+        self
+    }
+
+    /// Byte ranges of the matches of the current search
+    pub fn matches(&self) -> Vec<(usize, usize)> {
+        match *self.search.borrow() {
+            Some(ref search) => search.find_all(&self.text.borrow()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Move the caret to the next match, wrapping around at the end
+    pub fn find_next(&self) -> bool {
+        let matches = self.matches();
+        let text_i = self.text_i.get();
+        match matches.iter().find(|&&(start, _)| start > text_i).or(matches.first()) {
+            Some(&(start, _)) => {
+                self.text_i.set(start);
+                self.scroll_to_caret();
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Move the caret to the previous match, wrapping around at the start
+    pub fn find_previous(&self) -> bool {
+        let matches = self.matches();
+        let text_i = self.text_i.get();
+        match matches.iter().rev().find(|&&(start, _)| start < text_i).or(matches.last()) {
+            Some(&(start, _)) => {
+                self.text_i.set(start);
+                self.scroll_to_caret();
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Replace the match at the caret, and move to the next one
+    /// If the caret is not on a match, only moves to the next one.
+    pub fn replace(&self, replacement: &str) -> bool {
+        let text_i = self.text_i.get();
+        let current = self.matches().into_iter().find(|&(start, _)| start == text_i);
+        if let Some((start, end)) = current {
+            self.replace_range(start, end, replacement);
+        }
+        self.find_next() || current.is_some()
+    }
+
+    /// Replace all matches, returning how many were replaced
+    pub fn replace_all(&self, replacement: &str) -> usize {
+        let matches = self.matches();
+        {
+            let mut text = self.text.borrow_mut();
+            for &(start, end) in matches.iter().rev() {
+                text.drain(start..end);
+                text.insert_str(start, replacement);
+            }
+
+            let mut text_i = min(self.text_i.get(), text.len());
+            while !text.is_char_boundary(text_i) {
+                text_i -= 1;
+            }
+            self.text_i.set(text_i);
+        }
+        matches.len()
+    }
+
+    /// Scroll so the caret is in view
+    pub fn scroll_to_caret(&self) {
+        let text = self.text.borrow();
+        let layout = self.layout(&text);
+        let caret = self.caret_point(&text, &layout, self.text_i.get());
+        let view = layout.view;
+
+        let mut scroll_offset = self.scroll_offset.get();
+        if caret.x < scroll_offset.0 {
+            scroll_offset.0 = caret.x;
+        }
+        if caret.x + 8 > scroll_offset.0 + view.width as i32 {
+            scroll_offset.0 = caret.x + 8 - view.width as i32;
+        }
+        if caret.y < scroll_offset.1 {
+            scroll_offset.1 = caret.y;
+        }
+        if caret.y + 16 > scroll_offset.1 + view.height as i32 {
+            scroll_offset.1 = caret.y + 16 - view.height as i32;
+        }
+        self.scroll_offset.set(scroll_offset);
+
+        self.update_scroll_bars(&layout);
+    }
+
+    fn layout(&self, text: &str) -> Layout {
+        let rect = self.rect.get();
+        let text_offset = self.text_offset.get();
+        let multi_line = self.multi_line.get();
+        let tab_width = self.tab_width.get();
+
+        let gutter = if multi_line && self.line_numbers.get() {
+            (text.split('\n').count().to_string().len() as u32 + 1) * 8
+        } else {
+            0
+        };
+
+        let mut v_bar = false;
+        let mut h_bar = false;
+        loop {
+            let bar_width = if v_bar { SCROLL_BAR_SIZE as i32 + 1 } else { 0 };
+            let bar_height = if h_bar { SCROLL_BAR_SIZE as i32 + 1 } else { 0 };
+            let view = Rect::new(
+                rect.x + gutter as i32 + text_offset.x,
+                rect.y + text_offset.y,
+                max(0, rect.width as i32 - gutter as i32 - text_offset.x - bar_width) as u32,
+                max(0, rect.height as i32 - text_offset.y - bar_height) as u32
+            );
+
+            let wrap_width = if multi_line && self.word_wrap.get() {
+                Some(view.width as i32)
+            } else {
+                None
+            };
+
+            let lines = wrap_lines(text, wrap_width, tab_width);
+            let width = lines.iter().map(|line| text_width(&text[line.start..line.end], tab_width)).max().unwrap_or(0) + 8;
+            let height = lines.len() as i32 * 16;
+
+            if multi_line {
+                let need_v = height > view.height as i32;
+                let need_h = wrap_width.is_none() && width > view.width as i32;
+                if (need_v && !v_bar) || (need_h && !h_bar) {
+                    v_bar |= need_v;
+                    h_bar |= need_h;
+                    continue;
+                }
+            }
+
+            return Layout {
+                lines: lines,
+                view: view,
+                gutter: gutter,
+                width: width as u32,
+                height: height as u32,
+                v_bar: v_bar,
+                h_bar: h_bar,
+            };
+        }
+    }
+
+    /// Clamp the scroll offset to the laid out text, and place the scroll bars
+    fn update_scroll_bars(&self, layout: &Layout) {
+        let rect = self.rect.get();
+        let view = layout.view;
+
+        let mut scroll_offset = self.scroll_offset.get();
+        scroll_offset.0 = max(0, min(layout.width as i32 - view.width as i32, scroll_offset.0));
+        scroll_offset.1 = max(0, min(layout.height as i32 - view.height as i32, scroll_offset.1));
+        self.scroll_offset.set(scroll_offset);
+
+        let bar_width = if layout.v_bar { SCROLL_BAR_SIZE } else { 0 };
+        let bar_height = if layout.h_bar { SCROLL_BAR_SIZE } else { 0 };
+
+        self.v_scroll_bar.rect.set(Rect::new(
+            rect.x + rect.width as i32 - 1 - SCROLL_BAR_SIZE as i32, rect.y + 1,
+            SCROLL_BAR_SIZE, max(2 + bar_height, rect.height) - 2 - bar_height
+        ));
+        self.v_scroll_bar.range(layout.height, view.height).value(scroll_offset.1);
+
+        self.h_scroll_bar.rect.set(Rect::new(
+            rect.x + 1, rect.y + rect.height as i32 - 1 - SCROLL_BAR_SIZE as i32,
+            max(2 + bar_width, rect.width) - 2 - bar_width, SCROLL_BAR_SIZE
+        ));
+        self.h_scroll_bar.range(layout.width, view.width).value(scroll_offset.0);
+    }
+
+    /// Position of a character index, relative to the unscrolled text origin
+    fn caret_point(&self, text: &str, layout: &Layout, text_i: usize) -> Point {
+        let row = line_of(&layout.lines, text_i);
+        let line = layout.lines[row];
+        let glyphs = line_glyphs(text, &line, self.tab_width.get());
+        let line_x = line_x(&line, &glyphs, layout.view.width as i32);
+        let x = match glyphs.iter().find(|glyph| glyph.start == text_i) {
+            Some(glyph) => glyph.x,
+            // The end of a line is at its left side when reading right to left
+            None if line.rtl => -8,
+            None => glyphs.last().map_or(0, |glyph| glyph.x + glyph.width),
+        };
+        Point::new(line_x + x, row as i32 * 16)
+    }
+
+    /// Character index at a point relative to the unscrolled text origin
+    fn index_at(&self, text: &str, layout: &Layout, point: Point) -> usize {
+        let row = max(0, point.y) as usize / 16;
+        if row >= layout.lines.len() {
+            return text.len();
+        }
+
+        let line = layout.lines[row];
+        let glyphs = line_glyphs(text, &line, self.tab_width.get());
+        let x = point.x - line_x(&line, &glyphs, layout.view.width as i32);
+        for glyph in glyphs.iter() {
+            if x >= glyph.x && x < glyph.x + glyph.width {
+                return glyph.start;
+            }
+        }
+
+        // Past the left side of a right to left line is its end, and past its right side its start
+        if line.rtl == (x < 0) {
+            line.end
+        } else {
+            line.start
+        }
+    }
 }
 
 impl Border for TextBox {
@@ -157,11 +654,21 @@ impl Text for TextBox {
     }
 }
 
+impl Tooltip for TextBox {}
+
 impl Widget for TextBox {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, focused: bool) {
         if self.visible.get(){
             let rect = self.rect.get();
@@ -172,61 +679,167 @@ impl Widget for TextBox {
                 renderer.rounded_rect(rect.x, rect.y, rect.width, rect.height, b_r, false, self.fg_border);
             }
 
-            let text_i = self.text_i.get();
-            let text = self.text.borrow();
-
-            let text_offset = self.text_offset.get();
+            // The composing text of an input method is shown at the caret
+            let preedit = self.preedit.borrow();
+            let preedit_i = self.text_i.get();
+            let text_i = preedit_i + preedit.len();
+            let shift = |i: usize| if i >= preedit_i { i + preedit.len() } else { i };
+
+            let self_text = self.text.borrow();
+            let composed;
+            let text: &str = if preedit.is_empty() {
+                &self_text
+            } else {
+                let mut text = self_text.clone();
+                text.insert_str(preedit_i, &preedit);
+                composed = text;
+                &composed
+            };
+            let tab_width = self.tab_width.get();
+
+            let layout = self.layout(text);
+            self.update_scroll_bars(&layout);
+
+            let view = layout.view;
             let scroll_offset = self.scroll_offset.get();
 
-            let mut x = text_offset.x - scroll_offset.0 * 8;
-            let mut y = text_offset.y - scroll_offset.1 * 16;
-            let start_x = x;
-            for (i, c) in text.char_indices() {
-                let mut c_r = Rect::new(x + rect.x, y + rect.y, 8, 16);
-                if c == '\n' {
-                    if focused && i == text_i && rect.contains_rect(&c_r) {
-                        renderer.rect(x + rect.x, y + rect.y, 8, 16, self.fg_cursor);
-                    }
+            if layout.gutter > 0 {
+                renderer.rect(rect.x + 1, rect.y + 1, layout.gutter, max(2, rect.height) - 2, self.bg_gutter);
+            }
+
+            let cursor = if focused {
+                let caret = self.caret_point(&text, &layout, text_i);
+                let width = match text[text_i..].graphemes(true).next() {
+                    Some(g) if g != "\n" => grapheme_width(g, tab_width),
+                    _ => 8,
+                };
+                let c_r = Rect::new(view.x + caret.x - scroll_offset.0, view.y + caret.y - scroll_offset.1, width as u32, 16);
+                if rect.contains_rect(&c_r) && view.contains(c_r.point()) {
+                    Some(c_r)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            let mut spans: Vec<Span> = self.spans.borrow().iter()
+                .map(|span| Span::new(shift(span.start), shift(span.end), span.fg, span.bg))
+                .collect();
+            for (start, end) in self.matches() {
+                let bg = if start == preedit_i { self.bg_match_current } else { self.bg_match };
+                spans.push(Span::new(shift(start), shift(end), None, Some(bg)));
+            }
+            let mut cursor_drawn = false;
+            for (row, line) in layout.lines.iter().enumerate() {
+                let y = view.y + row as i32 * 16 - scroll_offset.1;
+                if y < view.y || y + 16 > view.y + view.height as i32 {
+                    continue;
+                }
 
-                    x = start_x;
-                    y += c_r.height as i32;
-                } else if c == '\t' {
-                    c_r.width = 8 * 4;
+                let line_spans: Vec<&Span> = spans.iter().filter(|span| span.end > line.start && span.start < line.end).collect();
 
-                    if focused && i == text_i && rect.contains_rect(&c_r) {
-                        renderer.rect(x + rect.x, y + rect.y, 8 * 4, 16, self.fg_cursor);
+                if layout.gutter > 0 && line.first {
+                    let number = format!("{}", line.number + 1);
+                    let mut x = rect.x + layout.gutter as i32 - 4 - number.len() as i32 * 8;
+                    for c in number.chars() {
+                        renderer.char(x, y, c, self.fg_gutter);
+                        x += 8;
                     }
+                }
 
-                    x += c_r.width as i32;
-                } else {
-                    if rect.contains_rect(&c_r) {
-                        if i == text_i && focused {
-                            renderer.rect(x + rect.x, y + rect.y, 8, 16, self.fg_cursor);
+                let glyphs = line_glyphs(text, line, tab_width);
+                let line_x = view.x - scroll_offset.0 + line_x(line, &glyphs, view.width as i32);
+                for glyph in glyphs.iter() {
+                    let i = glyph.start;
+                    let x = line_x + glyph.x;
+                    let c_r = Rect::new(x, y, glyph.width as u32, 16);
+                    if x >= view.x && rect.contains_rect(&c_r) && x + c_r.width as i32 <= view.x + view.width as i32 {
+                        let mut fg = self.fg;
+                        let mut bg = None;
+                        for span in line_spans.iter().filter(|span| span.start <= i && i < span.end) {
+                            fg = span.fg.unwrap_or(fg);
+                            bg = span.bg.or(bg);
                         }
-                        if let Some(mask_c) = self.mask_char.get() {
-                            renderer.char(x + rect.x, y + rect.y, mask_c, self.fg);
-                        } else {
-                            renderer.char(x + rect.x, y + rect.y, c, self.fg);
+
+                        if let Some(bg) = bg {
+                            renderer.rect(c_r.x, c_r.y, c_r.width, c_r.height, bg);
+                        }
+
+                        if let Some(cursor) = cursor {
+                            if cursor.x == x && cursor.y == y {
+                                renderer.rect(cursor.x, cursor.y, cursor.width, cursor.height, self.fg_cursor);
+                                cursor_drawn = true;
+                            }
+                        }
+
+                        let grapheme = &text[glyph.start..glyph.end];
+                        if grapheme != "\t" {
+                            if let Some(mask_c) = self.mask_char.get() {
+                                renderer.char(x, y, mask_c, fg);
+                            } else {
+                                // Combining marks are drawn over their base character
+                                for c in grapheme.chars() {
+                                    renderer.char(x, y, c, fg);
+                                }
+                            }
+                        }
+
+                        if i >= preedit_i && i < text_i {
+                            renderer.rect(x, y + 15, c_r.width, 1, fg);
                         }
                     }
+                }
+            }
 
-                    x += c_r.width as i32;
+            // The caret is past the end of its line
+            if let Some(cursor) = cursor {
+                if !cursor_drawn {
+                    renderer.rect(cursor.x, cursor.y, cursor.width, cursor.height, self.fg_cursor);
                 }
             }
 
-            let c_r = Rect::new(x + rect.x, y + rect.y, 8, 16);
-            if focused && text.len() == text_i && rect.contains_rect(&c_r) {
-                renderer.rect(x + rect.x, y + rect.y, 8, 16, self.fg_cursor);
+            if layout.v_bar {
+                self.v_scroll_bar.draw(renderer, false);
+            }
+            if layout.h_bar {
+                self.h_scroll_bar.draw(renderer, false);
             }
         }
     }
 
     fn event(&self, event: Event, mut focused: bool, redraw: &mut bool) -> bool {
+        // A composition is abandoned when the focus moves to another widget
+        if !focused && !self.preedit.borrow().is_empty() {
+            self.preedit.set(String::new());
+            *redraw = true;
+        }
+
         // If the event wasn't handled by the custom handler.
         if let Some(event) = self.handle_event(event, &mut focused, redraw) {
             let mut new_text_i = None;
             match event {
                 Event::Mouse { point, left_button, .. } => {
+                    let layout = {
+                        let text = self.text.borrow();
+                        self.layout(&text)
+                    };
+                    self.update_scroll_bars(&layout);
+
+                    let mut on_bar = false;
+                    if layout.v_bar && self.v_scroll_bar.event(event, false, redraw) {
+                        on_bar = true;
+                    }
+                    if layout.h_bar && self.h_scroll_bar.event(event, false, redraw) {
+                        on_bar = true;
+                    }
+
+                    if on_bar {
+                        self.scroll_offset.set((self.h_scroll_bar.value.get(), self.v_scroll_bar.value.get()));
+                        self.pressed.set(false);
+                        return focused;
+                    }
+
                     let mut click = false;
 
                     let rect = self.rect.get();
@@ -255,59 +868,22 @@ impl Widget for TextBox {
                         let click_point: Point = point - rect.point();
                         {
                             let text = self.text.borrow();
-
-                            let text_offset = self.text_offset.get();
                             let scroll_offset = self.scroll_offset.get();
-
-                            let mut x = text_offset.x - scroll_offset.0 * 8;
-                            let mut y = text_offset.y - scroll_offset.1 * 16;
-                            let start_x = x;
-                            for (i, c) in text.char_indices() {
-                                let mut c_r = Rect::new(x, y, 8, 16);
-                                if c == '\n' {
-                                    if click_point.x >= x && click_point.y >= y && click_point.y < (y + c_r.height as i32) {
-                                        new_text_i = Some(i);
-                                        break;
-                                    }
-
-                                    x = start_x;
-                                    y += c_r.height as i32;
-                                } else if c == '\t' {
-                                    c_r.width = 8 * 4;
-
-                                    if c_r.contains(click_point) {
-                                        new_text_i = Some(i);
-                                        break;
-                                    }
-
-                                    x += c_r.width as i32;
-                                } else {
-                                    if c_r.contains(click_point) {
-                                        new_text_i = Some(i);
-                                        break;
-                                    }
-
-                                    x += c_r.width as i32;
-                                }
-                            }
-
-                            let c_r = Rect::new(x, y, 8, 16);
-                            if (new_text_i.is_none() && click_point.x >= x && click_point.y >= y) || click_point.y >= (y + c_r.height as i32) {
-                                new_text_i = Some(text.len());
-                            }
+                            let text_point = point - layout.view.point() + Point::new(scroll_offset.0, scroll_offset.1);
+                            new_text_i = Some(self.index_at(&text, &layout, text_point));
                         }
 
                         self.emit_click(click_point);
                     }
                 }
                 Event::Scroll { y, .. } => {
-                    let lines = self.text.borrow().lines().count() as i32;
-                    let rows = (self.rect.get().height as i32 - self.text_offset.get().y)/16;
-
                     let mut scroll_offset = self.scroll_offset.get();
-                    scroll_offset.1 = max(0, min(lines - rows, scroll_offset.1 - y * 3));
+                    scroll_offset.1 -= y * 3 * 16;
                     self.scroll_offset.set(scroll_offset);
 
+                    let text = self.text.borrow();
+                    self.update_scroll_bars(&self.layout(&text));
+
                     *redraw = true;
                 }
                 Event::Text { c } => {
@@ -315,19 +891,42 @@ impl Widget for TextBox {
                         let mut text = self.text.borrow_mut();
                         let text_i = self.text_i.get();
                         text.insert(text_i, c);
-                        new_text_i = Some(next_i(text.deref(), text_i));
+                        new_text_i = Some(next_grapheme(text.deref(), text_i));
+                    }
+                }
+                Event::Preedit { c } => {
+                    if focused {
+                        self.preedit.borrow_mut().push(c);
+                        *redraw = true;
+                    }
+                }
+                Event::PreeditClear => {
+                    if focused && !self.preedit.borrow().is_empty() {
+                        self.preedit.set(String::new());
+                        *redraw = true;
+                    }
+                }
+                Event::Commit => {
+                    if focused {
+                        let preedit = self.preedit.get();
+                        self.preedit.set(String::new());
+                        if !preedit.is_empty() {
+                            let text_i = self.text_i.get();
+                            self.text.borrow_mut().insert_str(text_i, &preedit);
+                            new_text_i = Some(text_i + preedit.len());
+                        }
                     }
                 }
                 Event::Enter => {
                     if focused {
-                        if self.enter_callback.borrow().is_some() {
+                        if self.enter_callback.borrow().is_some() && ! self.multi_line.get() {
                             self.emit_enter();
                             *redraw = true;
                         } else {
                             let mut text = self.text.borrow_mut();
                             let text_i = self.text_i.get();
                             text.insert(text_i, '\n');
-                            new_text_i = Some(next_i(text.deref(), text_i));
+                            new_text_i = Some(next_grapheme(text.deref(), text_i));
                         }
                     }
                 }
@@ -337,11 +936,10 @@ impl Widget for TextBox {
                         let mut text_i = self.text_i.get();
 
                         if text_i > 0 {
-                            text_i = prev_i(text.deref(), text_i);
-                            if text_i < text.len() {
-                                text.remove(text_i);
-                                new_text_i = Some(min(text_i, text.len()));
-                            }
+                            let end = text_i;
+                            text_i = prev_grapheme(text.deref(), text_i);
+                            text.drain(text_i..end);
+                            new_text_i = Some(min(text_i, text.len()));
                         }
                     }
                 }
@@ -351,7 +949,8 @@ impl Widget for TextBox {
                         let text_i = self.text_i.get();
 
                         if text_i < text.len() {
-                            text.remove(text_i);
+                            let end = next_grapheme(text.deref(), text_i);
+                            text.drain(text_i..end);
                             new_text_i = Some(min(text_i, text.len()));
                         }
                     }
@@ -365,7 +964,7 @@ impl Widget for TextBox {
                             if text[.. text_i].chars().rev().next() == Some('\n') {
                                 break;
                             }
-                            text_i = prev_i(text.deref(), text_i);
+                            text_i = prev_grapheme(text.deref(), text_i);
                         }
 
                         new_text_i = Some(text_i);
@@ -380,7 +979,7 @@ impl Widget for TextBox {
                             if text[text_i ..].chars().next() == Some('\n') {
                                 break;
                             }
-                            text_i = next_i(text.deref(), text_i);
+                            text_i = next_grapheme(text.deref(), text_i);
                         }
 
                         new_text_i = Some(text_i);
@@ -389,73 +988,25 @@ impl Widget for TextBox {
                 Event::UpArrow => {
                     if focused {
                         let text = self.text.borrow();
-                        let mut text_i = self.text_i.get();
+                        let layout = self.layout(&text);
+                        let caret = self.caret_point(&text, &layout, self.text_i.get());
 
-                        // Count back to last newline
-                        let mut offset = 0;
-                        while text_i > 0 {
-                            let c = text[.. text_i].chars().rev().next();
-                            text_i = prev_i(text.deref(), text_i);
-                            if c == Some('\n') {
-                                break;
-                            }
-                            offset += 1;
+                        if caret.y >= 16 {
+                            new_text_i = Some(self.index_at(&text, &layout, Point::new(caret.x, caret.y - 16)));
                         }
-
-                        // Go to newline before last newline
-                        while text_i > 0 {
-                            if text[.. text_i].chars().rev().next() == Some('\n') {
-                                break;
-                            }
-                            text_i = prev_i(text.deref(), text_i);
-                        }
-
-                        // Add back offset
-                        while offset > 0 && text_i < text.len() {
-                            if text[text_i ..].chars().next() == Some('\n') {
-                                break;
-                            }
-                            text_i = next_i(text.deref(), text_i);
-                            offset -= 1;
-                        }
-
-                        new_text_i = Some(text_i);
                     }
                 }
                 Event::DownArrow => {
                     if focused {
                         let text = self.text.borrow();
-                        let mut text_i = self.text_i.get();
-
-                        // Count back to last newline
-                        let mut offset = 0;
-                        while text_i > 0 {
-                            if text[.. text_i].chars().rev().next() == Some('\n') {
-                                break;
-                            }
-                            text_i = prev_i(text.deref(), text_i);
-                            offset += 1;
-                        }
-
-                        // Go to next newline
-                        while text_i < text.len() {
-                            let c = text[text_i ..].chars().next();
-                            text_i = next_i(text.deref(), text_i);
-                            if c == Some('\n') {
-                                break;
-                            }
-                        }
+                        let layout = self.layout(&text);
+                        let caret = self.caret_point(&text, &layout, self.text_i.get());
 
-                        // Add back offset
-                        while offset > 0 && text_i < text.len() {
-                            if text[text_i ..].chars().next() == Some('\n') {
-                                break;
-                            }
-                            text_i = next_i(text.deref(), text_i);
-                            offset -= 1;
+                        if caret.y + 16 < layout.height as i32 {
+                            new_text_i = Some(self.index_at(&text, &layout, Point::new(caret.x, caret.y + 16)));
+                        } else {
+                            new_text_i = Some(text.len());
                         }
-
-                        new_text_i = Some(text_i);
                     }
                 }
                 Event::LeftArrow => {
@@ -464,7 +1015,7 @@ impl Widget for TextBox {
                         let text_i = self.text_i.get();
 
                         if text_i > 0 {
-                            new_text_i = Some(prev_i(text.deref(), text_i));
+                            new_text_i = Some(prev_grapheme(text.deref(), text_i));
                         }
                     }
                 }
@@ -473,7 +1024,7 @@ impl Widget for TextBox {
                         let text = self.text.borrow();
                         let text_i = self.text_i.get();
                         if text_i < text.len() {
-                            new_text_i = Some(next_i(text.deref(), text_i));
+                            new_text_i = Some(next_grapheme(text.deref(), text_i));
                         }
                     }
                 }
@@ -484,54 +1035,9 @@ impl Widget for TextBox {
                 self.text_i.set(text_i);
                 *redraw = true;
 
-                let text = self.text.borrow();
-
-                let text_offset = self.text_offset.get();
-                let mut scroll_offset = self.scroll_offset.get();
-
-                let mut col = 0;
-                let mut row = 0;
-                for (i, c) in text.char_indices() {
-                    if c == '\n' {
-                        if i == text_i {
-                            break;
-                        }
-
-                        col = 0;
-                        row += 1;
-                    } else if c == '\t' {
-                        if i == text_i {
-                            break;
-                        }
-
-                        col += 4;
-                    } else {
-                        if i == text_i {
-                            break;
-                        }
-
-                        col += 1;
-                    }
-                }
-
-                let rect = self.rect.get();
-                let cols = (rect.width as i32 - text_offset.x)/8;
-                let rows = (rect.height as i32 - text_offset.y)/16;
-
-                if col < scroll_offset.0 {
-                    scroll_offset.0 = col;
-                }
-                if col >= scroll_offset.0 + cols {
-                    scroll_offset.0 = col + 1 - cols;
-                }
-                if row < scroll_offset.1 {
-                    scroll_offset.1 = row;
-                }
-                if row >= scroll_offset.1 + rows {
-                    scroll_offset.1 = row + 1 - rows;
+                if self.auto_scroll.get() {
+                    self.scroll_to_caret();
                 }
-
-                self.scroll_offset.set(scroll_offset);
             }
 
             if self.grab_focus.check_set(false) {
@@ -550,3 +1056,56 @@ impl Widget for TextBox {
         Some("TextBox")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Line, Search, wrap_lines};
+
+    fn spans(lines: &[Line]) -> Vec<(usize, usize, usize, bool)> {
+        lines.iter().map(|line| (line.start, line.end, line.number, line.first)).collect()
+    }
+
+    #[test]
+    fn lines_split_at_newlines() {
+        assert_eq!(spans(&wrap_lines("ab\n\ncd", None, 4)), vec![(0, 2, 0, true), (3, 3, 1, true), (4, 6, 2, true)]);
+        assert_eq!(spans(&wrap_lines("", None, 4)), vec![(0, 0, 0, true)]);
+    }
+
+    #[test]
+    fn lines_wrap_after_whitespace() {
+        // Five characters fit in 40 pixels
+        assert_eq!(spans(&wrap_lines("aaa bbb\nc", Some(40), 4)), vec![(0, 4, 0, true), (4, 7, 0, false), (8, 9, 1, true)]);
+        assert_eq!(spans(&wrap_lines("a\tb", Some(40), 4)), vec![(0, 2, 0, true), (2, 3, 0, false)]);
+    }
+
+    #[test]
+    fn long_words_wrap_anywhere() {
+        assert_eq!(spans(&wrap_lines("abcdefgh", Some(24), 4)), vec![(0, 3, 0, true), (3, 6, 0, false), (6, 8, 0, false)]);
+    }
+
+    #[test]
+    fn search_ignores_case_by_default() {
+        assert_eq!(Search::new("ab").find_all("Ab ab aB"), vec![(0, 2), (3, 5), (6, 8)]);
+        assert_eq!(Search::new("ab").case_sensitive(true).find_all("Ab ab aB"), vec![(3, 5)]);
+        // Byte ranges of characters longer than a byte
+        assert_eq!(Search::new("är").find_all("Ärger är"), vec![(0, 3), (7, 10)]);
+    }
+
+    #[test]
+    fn search_whole_words() {
+        assert_eq!(Search::new("ab").whole_word(true).find_all("ab abc cab ab_ ab"), vec![(0, 2), (15, 17)]);
+    }
+
+    #[test]
+    fn search_matches_do_not_overlap() {
+        assert_eq!(Search::new("aa").find_all("aaaaa"), vec![(0, 2), (2, 4)]);
+        assert_eq!(Search::new("").find_all("aaaaa"), vec![]);
+    }
+
+    #[test]
+    fn lines_know_their_direction() {
+        let lines = wrap_lines("abc\n\u{5d0}\u{5d1} abc", None, 4);
+        assert!(!lines[0].rtl);
+        assert!(lines[1].rtl);
+    }
+}
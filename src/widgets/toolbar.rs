@@ -3,79 +3,480 @@
 use orbclient::{Color, Renderer};
 use orbimage;
 use std::cell::{Cell, RefCell};
-use cell::CloneCell;
+use std::cmp::max;
+use std::i32;
+use cell::{CheckSet, CloneCell};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use theme::{LABEL_BACKGROUND, LABEL_BORDER, LABEL_FOREGROUND};
 use event::Event;
 use point::Point;
 use rect::Rect;
-use traits::{Click, Place, Text}; //TODO create traits Tooltip , for now use Text
-use widgets::Widget;
-use window::Window;
+use traits::{Click, Place, Tooltip};
+use widgets::{own_tooltip_at, Action, Menu, Orientation, Overlay, Separator, Widget};
 
-#[allow(unused_imports)]
-use std::time::{Duration, Instant};
+/// Length of the chevron opening the overflow menu
+const CHEVRON_SIZE: i32 = 12;
 
-#[allow(dead_code)]
-#[derive(Clone)]
+/// How toolbar icons show their image and text
+/// The text of an icon is its tooltip text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ToolbarMode {
+    IconOnly,
+    TextOnly,
+    TextBesideIcon,
+    TextUnderIcon,
+}
+
+/// An item of a toolbar
+enum ToolbarItem {
+    Icon(Arc<ToolbarIcon>),
+    Separator,
+}
+
+/// A row or column of icons, laid out from the toolbar position
 pub struct Toolbar {
-    //parent : RefCell<*mut Window>,
-    pub items : RefCell<Vec<Arc<ToolbarIcon>>>,
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    pub fg_separator: Color,
+    items: RefCell<Vec<ToolbarItem>>,
+    pub orientation: Cell<Orientation>,
+    /// Space between items, and around them
+    pub spacing: Cell<u32>,
+    pub mode: Cell<ToolbarMode>,
+    /// Index of the first item which does not fit, and is in the overflow menu instead
+    overflow: Cell<Option<usize>>,
+    overflow_menu: RefCell<Option<Arc<Menu>>>,
+    chevron_pressed: Cell<bool>,
+    selection_changed_callback: RefCell<Option<Arc<Fn(&Toolbar, &Arc<ToolbarIcon>)>>>,
+    /// The toolbar itself, for the actions of the overflow menu
+    this: RefCell<Weak<Toolbar>>,
     pub visible: Cell<bool>,
-    pub selected: Cell<bool>,
-    //pub rect: Cell<Rect>,
-    
 }
-#[allow(dead_code)]
+
 impl Toolbar {
-    pub fn new() -> Self {
-        Toolbar{
-            items : RefCell::new(Vec::new()),
+    pub fn new() -> Arc<Self> {
+        let toolbar = Arc::new(Toolbar {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: LABEL_BACKGROUND,
+            fg_separator: LABEL_BORDER,
+            items: RefCell::new(Vec::new()),
+            orientation: Cell::new(Orientation::Horizontal),
+            spacing: Cell::new(2),
+            mode: Cell::new(ToolbarMode::IconOnly),
+            overflow: Cell::new(None),
+            overflow_menu: RefCell::new(None),
+            chevron_pressed: Cell::new(false),
+            selection_changed_callback: RefCell::new(None),
+            this: RefCell::new(Weak::new()),
             visible: Cell::new(true),
-            selected : Cell::new(true)
-        }
+        });
+        *toolbar.this.borrow_mut() = Arc::downgrade(&toolbar);
+        toolbar
     }
-    
-    pub fn add(&self, toolbar_icon: &Arc<ToolbarIcon>, window:*mut Window ) -> usize {
-        let mut items = self.items.borrow_mut();
-        let id = items.len();
-        items.push(toolbar_icon.clone());
-        //add also to parent window
-        unsafe{(&mut *window).add(&toolbar_icon.clone());}
+
+    /// Adds an icon, returning its index among the icons
+    pub fn add(&self, toolbar_icon: &Arc<ToolbarIcon>) -> usize {
+        let id = self.icons().len();
+        self.items.borrow_mut().push(ToolbarItem::Icon(toolbar_icon.clone()));
+        self.arrange();
         id
     }
-    
-    pub fn visible (&self, v: bool) {
-        //set visibility for all items of toolbar 
-        let items = self.items.borrow_mut();
-        for i in 0..items.len(){
-            if let Some(tolbar_icon) = items.get(i) {
-                tolbar_icon.visible.set(v);
-            }
-        }
+
+    /// Adds a line between the icons before and after it
+    pub fn add_separator(&self) {
+        self.items.borrow_mut().push(ToolbarItem::Separator);
+        self.arrange();
     }
-    
+
+    pub fn orientation(&self, orientation: Orientation) -> &Self {
+        self.orientation.set(orientation);
+        self.arrange();
+        self
+    }
+
+    pub fn spacing(&self, spacing: u32) -> &Self {
+        self.spacing.set(spacing);
+        self.arrange();
+        self
+    }
+
+    pub fn mode(&self, mode: ToolbarMode) -> &Self {
+        self.mode.set(mode);
+        self.arrange();
+        self
+    }
+
+    pub fn icons(&self) -> Vec<Arc<ToolbarIcon>> {
+        self.items.borrow().iter().filter_map(|item| match *item {
+            ToolbarItem::Icon(ref icon) => Some(icon.clone()),
+            ToolbarItem::Separator => None,
+        }).collect()
+    }
+
+    /// The icons which fit in the toolbar, and are not in the overflow menu
+    fn shown_icons(&self) -> Vec<Arc<ToolbarIcon>> {
+        let items = self.items.borrow();
+        let end = self.overflow.get().unwrap_or(items.len());
+        items[..end].iter().filter_map(|item| match *item {
+            ToolbarItem::Icon(ref icon) => Some(icon.clone()),
+            ToolbarItem::Separator => None,
+        }).collect()
+    }
+
     pub fn enabled (&self, e: bool) {
-        let items = self.items.borrow_mut();
-        for i in 0..items.len(){
-            if let Some(tolbar_icon) = items.get(i) {
-                tolbar_icon.enabled.set(e);
-            }
+        for toolbar_icon in self.icons() {
+            toolbar_icon.enabled.set(e);
         }
     }
     
     pub fn toggle (&self) {
         //deselect all items from toolbar 
-        let items = self.items.borrow_mut();
-        for i in 0..items.len(){
-            if let Some(toolbar_icon) = items.get(i) {
-                toolbar_icon.selected(false);
+        for toolbar_icon in self.icons() {
+            toolbar_icon.selected(false);
+        }
+    }
+
+    /// Called with the icon whose selection was changed, by a click or by `select`
+    pub fn on_selection_changed<T: Fn(&Self, &Arc<ToolbarIcon>) + 'static>(&self, func: T) -> &Self {
+        *self.selection_changed_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_selection_changed(&self, toolbar_icon: &Arc<ToolbarIcon>) {
+        let callback = self.selection_changed_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self, toolbar_icon);
+        }
+    }
+
+    /// Select or deselect an icon, deselecting the rest of its group
+    pub fn select(&self, toolbar_icon: &Arc<ToolbarIcon>, flag: bool) {
+        if toolbar_icon.selected.check_set(flag) {
+            if flag {
+                self.exclude(toolbar_icon);
             }
+            self.emit_selection_changed(toolbar_icon);
         }
-    }    
+    }
+
+    /// The selected icon of an exclusive group, which is the active tool
+    pub fn active(&self, group: &str) -> Option<Arc<ToolbarIcon>> {
+        self.icons().into_iter().find(|icon| {
+            icon.selected.get() && icon.radio_group.get().map_or(false, |g| g == group)
+        })
+    }
+
+    /// Deselect the other icons in the group of a selected icon
+    fn exclude(&self, toolbar_icon: &Arc<ToolbarIcon>) {
+        if let Some(group) = toolbar_icon.radio_group.get() {
+            for icon in self.icons() {
+                if !Arc::ptr_eq(&icon, toolbar_icon) && icon.radio_group.get().as_ref() == Some(&group) {
+                    icon.selected.set(false);
+                }
+            }
+        }
+    }
+
+    /// Place the icons one after the other, with separators taking twice the spacing
+    /// The items which do not fit go to the overflow menu, leaving room for its chevron.
+    fn arrange(&self) {
+        let rect = self.rect.get();
+        let spacing = self.spacing.get() as i32;
+        let horizontal = self.orientation.get() == Orientation::Horizontal;
+        let mode = self.mode.get();
+
+        let items = self.items.borrow();
+        let lengths: Vec<i32> = items.iter().map(|item| match *item {
+            ToolbarItem::Icon(ref icon) => {
+                icon.fit(mode);
+                let icon_rect = icon.rect.get();
+                if horizontal { icon_rect.width as i32 } else { icon_rect.height as i32 }
+            },
+            ToolbarItem::Separator => spacing + 1,
+        }).collect();
+
+        // A toolbar without a size grows with its items
+        let length = if horizontal { rect.width } else { rect.height } as i32;
+        let total = lengths.iter().fold(spacing, |total, length| total + length + spacing);
+        let limit = if length > 0 && total > length {
+            length - CHEVRON_SIZE - 2 * spacing
+        } else {
+            i32::MAX
+        };
+
+        let mut overflow = None;
+        let mut offset = spacing;
+        for (i, (item, &item_length)) in items.iter().zip(lengths.iter()).enumerate() {
+            if offset + item_length > limit {
+                overflow = Some(i);
+                break;
+            }
+            if let ToolbarItem::Icon(ref icon) = *item {
+                let mut icon_rect = icon.rect.get();
+                if horizontal {
+                    icon_rect.x = rect.x + offset;
+                    icon_rect.y = rect.y + spacing;
+                } else {
+                    icon_rect.x = rect.x + spacing;
+                    icon_rect.y = rect.y + offset;
+                }
+                icon.rect.set(icon_rect);
+            }
+            offset += item_length + spacing;
+        }
+        self.overflow.set(overflow);
+    }
+
+    /// Where the chevron opening the overflow menu is, at the end of the toolbar
+    fn chevron_rect(&self) -> Rect {
+        let rect = self.rect.get();
+        let spacing = self.spacing.get() as i32;
+        match self.orientation.get() {
+            Orientation::Horizontal => Rect::new(rect.x + rect.width as i32 - CHEVRON_SIZE - spacing, rect.y + spacing,
+                                                 CHEVRON_SIZE as u32, max(0, rect.height as i32 - 2 * spacing) as u32),
+            Orientation::Vertical => Rect::new(rect.x + spacing, rect.y + rect.height as i32 - CHEVRON_SIZE - spacing,
+                                               max(0, rect.width as i32 - 2 * spacing) as u32, CHEVRON_SIZE as u32),
+        }
+    }
+
+    fn draw_chevron(&self, renderer: &mut Renderer) {
+        let chevron = self.chevron_rect();
+        let center_x = chevron.x + chevron.width as i32 / 2;
+        let center_y = chevron.y + chevron.height as i32 / 2;
+        if self.chevron_pressed.get() {
+            renderer.rounded_rect(chevron.x, chevron.y, chevron.width, chevron.height, 3, false, self.fg_separator);
+        }
+        // Two arrows pointing along the toolbar
+        for &shift in [-3, 2].iter() {
+            for i in 0..3 {
+                match self.orientation.get() {
+                    Orientation::Horizontal => renderer.rect(center_x + shift + i, center_y - 3 + i, 1, (7 - 2 * i) as u32, LABEL_FOREGROUND),
+                    Orientation::Vertical => renderer.rect(center_x - 3 + i, center_y + shift + i, (7 - 2 * i) as u32, 1, LABEL_FOREGROUND),
+                }
+            }
+        }
+    }
+
+    /// Pop up a menu with the items which do not fit, below or beside the chevron
+    fn open_overflow(&self) {
+        let start = match self.overflow.get() {
+            Some(start) => start,
+            None => return,
+        };
+
+        let menu = Menu::new("");
+        for item in self.items.borrow()[start..].iter() {
+            match *item {
+                ToolbarItem::Icon(ref icon) => {
+                    let action = Action::new(icon.tooltip_text.get());
+                    action.icon(icon.image.borrow().clone());
+                    if let Some(group) = icon.radio_group.get() {
+                        action.radio(group);
+                    } else {
+                        action.checkable(icon.checkable.get());
+                    }
+                    action.checked(icon.selected.get())
+                        .enabled(icon.enabled.get());
+
+                    let toolbar = self.this.borrow().clone();
+                    let icon = icon.clone();
+                    action.on_click(move |_action: &Action, _point: Point| {
+                        if let Some(toolbar) = toolbar.upgrade() {
+                            toolbar.select(&icon, icon.chosen());
+                        }
+                        icon.emit_click(Point::default());
+                    });
+                    menu.add(&action);
+                },
+                ToolbarItem::Separator => menu.add(&Separator::new()),
+            }
+        }
+
+        let chevron = self.chevron_rect();
+        menu.popup(match self.orientation.get() {
+            Orientation::Horizontal => Point::new(chevron.x, chevron.y + chevron.height as i32),
+            Orientation::Vertical => Point::new(chevron.x + chevron.width as i32, chevron.y),
+        });
+        *self.overflow_menu.borrow_mut() = Some(menu);
+    }
+
+    fn open_overflow_menu(&self) -> Option<Arc<Menu>> {
+        self.overflow_menu.borrow().clone().and_then(|menu| if menu.is_open() { Some(menu) } else { None })
+    }
+
+    /// Size fitting all the items
+    pub fn content_size(&self) -> (u32, u32) {
+        let spacing = self.spacing.get();
+        let horizontal = self.orientation.get() == Orientation::Horizontal;
+        let mode = self.mode.get();
+
+        let (mut along, mut across) = (spacing, 0);
+        for item in self.items.borrow().iter() {
+            let (item_along, item_across) = match *item {
+                ToolbarItem::Icon(ref icon) => {
+                    icon.fit(mode);
+                    let icon_rect = icon.rect.get();
+                    if horizontal { (icon_rect.width, icon_rect.height) } else { (icon_rect.height, icon_rect.width) }
+                },
+                ToolbarItem::Separator => (spacing + 1, 0),
+            };
+            along += item_along + spacing;
+            across = max(across, item_across);
+        }
+        across += 2 * spacing;
+
+        if horizontal { (along, across) } else { (across, along) }
+    }
 }
 
+impl Place for Toolbar {
+    fn position(&self, x: i32, y: i32) -> &Self {
+        let mut rect = self.rect().get();
+        rect.x = x;
+        rect.y = y;
+        self.rect().set(rect);
+
+        self.arrange();
+
+        self
+    }
+}
+
+impl Tooltip for Toolbar {}
+
+impl Widget for Toolbar {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        if !self.visible.get() {
+            return;
+        }
+
+        self.arrange();
+        let rect = self.rect.get();
+        renderer.rect(rect.x, rect.y, rect.width, rect.height, self.bg);
+
+        let spacing = self.spacing.get() as i32;
+        let items = self.items.borrow();
+        let shown = self.overflow.get().unwrap_or(items.len());
+        let mut end = 0;
+        for item in items[..shown].iter() {
+            match *item {
+                ToolbarItem::Icon(ref icon) => {
+                    icon.draw(renderer, focused);
+                    let icon_rect = icon.rect.get();
+                    end = match self.orientation.get() {
+                        Orientation::Horizontal => icon_rect.x + icon_rect.width as i32 - rect.x,
+                        Orientation::Vertical => icon_rect.y + icon_rect.height as i32 - rect.y,
+                    };
+                },
+                ToolbarItem::Separator => {
+                    let at = end + spacing + spacing / 2;
+                    match self.orientation.get() {
+                        Orientation::Horizontal => renderer.rect(rect.x + at, rect.y + spacing, 1, max(0, rect.height as i32 - 2 * spacing) as u32, self.fg_separator),
+                        Orientation::Vertical => renderer.rect(rect.x + spacing, rect.y + at, max(0, rect.width as i32 - 2 * spacing) as u32, 1, self.fg_separator),
+                    }
+                    end += 2 * spacing + 1;
+                },
+            }
+        }
+
+        if self.overflow.get().is_some() {
+            self.draw_chevron(renderer);
+        }
+    }
+
+    fn draw_overlay(&self, renderer: &mut Renderer) {
+        if let Some(menu) = self.open_overflow_menu() {
+            menu.draw_overlay(renderer);
+        }
+    }
+
+    fn overlay(&self) -> Option<Overlay> {
+        self.open_overflow_menu().and_then(|menu| menu.overlay())
+    }
+
+    fn tooltip_at(&self, point: Point) -> Option<String> {
+        if !self.visible.get() || !self.rect.get().contains(point) {
+            return None;
+        }
+        self.shown_icons().iter().filter_map(|icon| icon.tooltip_at(point)).next()
+            .or_else(|| own_tooltip_at(self, point))
+    }
+
+    fn event(&self, event: Event, focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        self.arrange();
+
+        // Icons chosen from the overflow menu are selected by its actions
+        if let Some(menu) = self.open_overflow_menu() {
+            menu.event(event, focused, redraw);
+            return focused;
+        }
+
+        let icons = self.icons();
+        let was_selected: Vec<bool> = icons.iter().map(|icon| icon.selected.get()).collect();
+
+        for icon in self.shown_icons() {
+            icon.event(event, focused, redraw);
+        }
+
+        if let Event::Mouse { point, left_button, .. } = event {
+            // The chevron opens the overflow menu on release, like a button
+            let over = self.overflow.get().is_some() && self.chevron_rect().contains(point);
+            if over && left_button {
+                if self.chevron_pressed.check_set(true) {
+                    *redraw = true;
+                }
+            } else if !left_button && self.chevron_pressed.check_set(false) {
+                if over {
+                    self.open_overflow();
+                }
+                *redraw = true;
+            }
+        }
+
+        // Report the icons changed by the event, after deselecting the rest of their groups
+        let changed: Vec<Arc<ToolbarIcon>> = icons.into_iter().zip(was_selected)
+            .filter(|&(ref icon, was_selected)| icon.selected.get() != was_selected)
+            .map(|(icon, _)| icon)
+            .collect();
+        for icon in changed.iter() {
+            if icon.selected.get() {
+                self.exclude(icon);
+            }
+        }
+        for icon in changed.iter() {
+            self.emit_selection_changed(icon);
+        }
+
+        focused
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("Toolbar")
+    }
+}
 
 pub struct ToolbarIcon {
     pub rect: Cell<Rect>,
@@ -84,17 +485,15 @@ pub struct ToolbarIcon {
     pub visible: Cell<bool>,
     pub enabled: Cell<bool>,
     pub selected: Cell<bool>,
-    pub tooltip: Cell<bool>,
+    /// Whether a click toggles the selection, instead of the icon acting as a momentary button
+    pub checkable: Cell<bool>,
+    radio_group: CloneCell<Option<String>>,
+    pressed: Cell<bool>,
+    mode: Cell<ToolbarMode>,
+    /// Shown as the label of the icon by the text modes of its toolbar
     pub tooltip_text: CloneCell<String>,
-    pub tooltip_offset: Cell<Point>,
     pub bg: Cell<Color>,
     pub fg: Cell<Color>,
-    pub fg_border: Cell<Color>,
-    pub border: Cell<bool>,
-    pub border_radius: Cell<u32>,
-    tooltip_time : Cell<Option<Instant>>,
-    
- 
 }
 
 impl ToolbarIcon {
@@ -114,16 +513,13 @@ impl ToolbarIcon {
             visible: Cell::new(true),
             enabled: Cell::new(true),
             selected: Cell::new(false),
-            tooltip: Cell::new(false),
+            checkable: Cell::new(false),
+            radio_group: CloneCell::new(None),
+            pressed: Cell::new(false),
+            mode: Cell::new(ToolbarMode::IconOnly),
             tooltip_text: CloneCell::new(String::new()),
-            tooltip_offset: Cell::new(Point::default()),
             bg: Cell::new(LABEL_BACKGROUND),
             fg: Cell::new(LABEL_FOREGROUND),
-            fg_border: Cell::new(LABEL_BORDER),
-            border: Cell::new(true),
-            border_radius: Cell::new(0),
-            tooltip_time : Cell::new(None),
-            
         })
     }
 
@@ -138,6 +534,50 @@ impl ToolbarIcon {
     pub fn enabled(&self, flag: bool) {
         self.enabled.set(flag);
     }
+
+    pub fn checkable(&self, checkable: bool) -> &Self {
+        self.checkable.set(checkable);
+        self
+    }
+
+    /// Make the icon one of a group in its toolbar, of which only one is selected
+    pub fn radio<S: Into<String>>(&self, group: S) -> &Self {
+        self.checkable.set(true);
+        self.radio_group.set(Some(group.into()));
+        self
+    }
+
+    /// Size the icon to show its image and text as in the toolbar mode
+    fn fit(&self, mode: ToolbarMode) {
+        self.mode.set(mode);
+        let (image_width, image_height) = {
+            let image = self.image.borrow();
+            (image.width(), image.height())
+        };
+        let text_width = self.tooltip_text.get().chars().count() as u32 * 8;
+
+        let (width, height) = match mode {
+            ToolbarMode::IconOnly => (image_width, image_height),
+            ToolbarMode::TextOnly => (text_width + 8, 20),
+            ToolbarMode::TextBesideIcon => (image_width + text_width + 8, max(image_height, 16)),
+            ToolbarMode::TextUnderIcon => (max(image_width, text_width), image_height + 16),
+        };
+        let mut rect = self.rect.get();
+        rect.width = width;
+        rect.height = height;
+        self.rect.set(rect);
+    }
+
+    /// Selected state after a click, as a toggle, radio or momentary button
+    fn chosen(&self) -> bool {
+        if self.radio_group.get().is_some() {
+            true
+        } else if self.checkable.get() {
+            !self.selected.get()
+        } else {
+            self.selected.get()
+        }
+    }
 }
 
 impl Click for ToolbarIcon {
@@ -155,62 +595,49 @@ impl Click for ToolbarIcon {
 
 impl Place for ToolbarIcon {}
 
-// TODO create new traits Tooltip , for now workaround using Text
-impl Text for ToolbarIcon {
-    fn text<S: Into<String>>(&self, text: S) -> &Self {
-        self.tooltip_text.set(text.into());
-        self
-    }
-
-    fn text_offset(&self, x: i32, y: i32) -> &Self {
-        self.tooltip_offset.set(Point::new(x, y));
-        self
-    }
-}
+impl Tooltip for ToolbarIcon {}
 
 impl Widget for ToolbarIcon {
     fn rect(&self) -> &Cell<Rect> {
         &self.rect
     }
 
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     fn draw(&self, renderer: &mut Renderer, _focused: bool) {
         if self.visible.get(){
-            let mut rect = self.rect.get();
+            let rect = self.rect.get();
             let image = self.image.borrow();
-            renderer.image(rect.x, rect.y, image.width(), image.height(), image.data());
-            if self.selected.get(){
-                renderer.rounded_rect(rect.x,rect.y, image.width()+1,image.height()+1,3,false,Color::rgb(0, 0, 0));
-            }
-        
-    
-    //draw tooltip
-            
-        if self.tooltip.get(){
-            let text = self.tooltip_text.borrow();
-            rect = Rect::new(rect.x, rect.y+rect.height as i32, 8* text.len() as u32, 16); 
-            let b_r = self.border_radius.get();
-            let bg = Color::rgb(255,255,100); //yellow tooltip background
-            renderer.rounded_rect(rect.x, rect.y, rect.width, rect.height, b_r, true, bg);
-            if self.border.get() {
-                renderer.rounded_rect(rect.x, rect.y, rect.width, rect.height, b_r, false, self.fg_border.get());
-            }
-
-            let fg = self.fg.get();
-            
+            let label = self.tooltip_text.get();
+            let (image_width, image_height) = (image.width() as i32, image.height() as i32);
+            let label_width = label.chars().count() as i32 * 8;
+            let (width, height) = (rect.width as i32, rect.height as i32);
 
-            let mut point = self.tooltip_offset.get();
-            for c in text.chars() {
-                if c == '\n' {
-                    point.x = self.tooltip_offset.get().x;
-                    point.y += 16;
-                } else {
-                    if point.x + 8 <= rect.width as i32 && point.y + 16 <= rect.height as i32 {
-                        renderer.char(point.x + rect.x, point.y + rect.y, c, fg);
-                    }
-                    point.x += 8;
+            let (image_point, label_point) = match self.mode.get() {
+                ToolbarMode::IconOnly => (Some(rect.point()), None),
+                ToolbarMode::TextOnly => (None, Some(Point::new(rect.x + 4, rect.y + (height - 16) / 2))),
+                ToolbarMode::TextBesideIcon => (Some(Point::new(rect.x, rect.y + (height - image_height) / 2)),
+                                                Some(Point::new(rect.x + image_width + 4, rect.y + (height - 16) / 2))),
+                ToolbarMode::TextUnderIcon => (Some(Point::new(rect.x + (width - image_width) / 2, rect.y)),
+                                               Some(Point::new(rect.x + (width - label_width) / 2, rect.y + image_height))),
+            };
+            if let Some(point) = image_point {
+                renderer.image(point.x, point.y, image.width(), image.height(), image.data());
+            }
+            if let Some(point) = label_point {
+                for (i, c) in label.chars().enumerate() {
+                    renderer.char(point.x + i as i32 * 8, point.y, c, self.fg.get());
                 }
             }
-         }
+            if self.selected.get() || self.pressed.get() {
+                renderer.rounded_rect(rect.x,rect.y, rect.width+1,rect.height+1,3,false,Color::rgb(0, 0, 0));
+            }
         }
     }
 
@@ -218,51 +645,27 @@ impl Widget for ToolbarIcon {
         if self.visible.get() & self.enabled.get() {
             
             match event {
-                Event::Mouse { point, left_button, right_button, .. } => {
+                Event::Mouse { point, left_button, .. } => {
                     let rect = self.rect.get();
                     if rect.contains(point) && left_button {
-                        let click_point: Point = point - rect.point();
-                        self.emit_click(click_point);
-                        if self.selected.get() {
-                            self.selected.set(false);
-                        } else {
-                            self.selected.set(true);
+                        if self.pressed.check_set(true) {
+                            *redraw = true;
+                        }
+                    } else if !left_button && self.pressed.check_set(false) {
+                        if rect.contains(point) {
+                            self.selected.set(self.chosen());
+                            let click_point: Point = point - rect.point();
+                            self.emit_click(click_point);
                         }
-                        *redraw = true;
-                    }
-                    if rect.contains(point) && right_button {
-                        self.selected.set(false);
-                        *redraw = true;
-                    }
-                    if rect.contains(point) {
-                        //FIXME after 2 sec shows up tooltip but only if mouse is moved 
-                            match self.tooltip_time.get() {
-                                Some(time) => {
-                                                if !self.tooltip.get(){
-                                                    if (Instant::now()-time) > Duration::new(2,0){
-                                                        //println!("Tooltip: {} time:{:?}",self.tooltip_text.get(),time);
-                                                        self.tooltip.set(true);
-                                                        *redraw = true;
-                                                        }
-                                                    }
-                                                },
-                                None       => self.tooltip_time.set(Some(Instant::now())),
-                            }
-                        //self.tooltip.set(true);
-                        //*redraw = true;
-                    }else{
-                        self.tooltip_time.set(None);
-                        self.tooltip.set(false);
                         *redraw = true;
                     }
                 }
-                    
                 _ => (),
             }
         }
         focused
     }
-    
+
     fn visible(&self, flag: bool) {
         self.visible.set(flag);
     }
@@ -272,4 +675,124 @@ impl Widget for ToolbarIcon {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use event::Event;
+    use point::Point;
+    use traits::{Place, Tooltip};
+    use widgets::{Orientation, Widget};
+    use super::{Toolbar, ToolbarIcon, ToolbarMode};
+
+    fn toolbar() -> (Arc<Toolbar>, Arc<ToolbarIcon>, Arc<ToolbarIcon>) {
+        let toolbar = Toolbar::new();
+        let first = ToolbarIcon::new(16, 16);
+        first.tooltip("Pen");
+        let second = ToolbarIcon::new(16, 16);
+        toolbar.add(&first);
+        toolbar.add_separator();
+        toolbar.add(&second);
+        toolbar.position(10, 20);
+        (toolbar, first, second)
+    }
+
+    fn bounds(icon: &ToolbarIcon) -> (i32, i32, u32, u32) {
+        let rect = icon.rect.get();
+        (rect.x, rect.y, rect.width, rect.height)
+    }
+
+    #[test]
+    fn arrange_horizontally() {
+        let (toolbar, first, second) = toolbar();
+        assert_eq!(bounds(&first), (12, 22, 16, 16));
+        // The separator takes the spacing and a line
+        assert_eq!(bounds(&second), (35, 22, 16, 16));
+        assert_eq!(toolbar.content_size(), (43, 20));
+    }
+
+    #[test]
+    fn arrange_vertically() {
+        let (toolbar, first, second) = toolbar();
+        toolbar.orientation(Orientation::Vertical);
+        assert_eq!(bounds(&first), (12, 22, 16, 16));
+        assert_eq!(bounds(&second), (12, 45, 16, 16));
+        assert_eq!(toolbar.content_size(), (20, 43));
+    }
+
+    #[test]
+    fn arrange_with_text() {
+        let (toolbar, first, second) = toolbar();
+        toolbar.mode(ToolbarMode::TextBesideIcon);
+        assert_eq!(bounds(&first), (12, 22, 48, 16));
+        assert_eq!(bounds(&second), (67, 22, 24, 16));
+    }
+
+    /// Record the tooltips of the icons whose selection changed
+    fn record(toolbar: &Toolbar) -> Rc<RefCell<Vec<String>>> {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let changes_clone = changes.clone();
+        toolbar.on_selection_changed(move |_toolbar: &Toolbar, icon: &Arc<ToolbarIcon>| {
+            changes_clone.borrow_mut().push(icon.tooltip_text.get());
+        });
+        changes
+    }
+
+    fn click(toolbar: &Toolbar, icon: &ToolbarIcon) {
+        let point = icon.rect.get().point() + Point::new(1, 1);
+        let mut redraw = false;
+        for &left_button in [true, false].iter() {
+            toolbar.event(Event::Mouse { point: point, left_button: left_button, middle_button: false, right_button: false },
+                          false, &mut redraw);
+        }
+    }
+
+    #[test]
+    fn select_excludes_the_group() {
+        let (toolbar, first, second) = toolbar();
+        first.radio("tool");
+        second.radio("tool").tooltip("Brush");
+        let changes = record(&toolbar);
+
+        toolbar.select(&first, true);
+        toolbar.select(&second, true);
+        assert!(!first.selected.get());
+        assert!(toolbar.active("tool").map_or(false, |icon| Arc::ptr_eq(&icon, &second)));
+        assert!(toolbar.active("other").is_none());
+        // Selecting a selected icon changes nothing
+        toolbar.select(&second, true);
+        assert_eq!(*changes.borrow(), vec!["Pen", "Brush"]);
+    }
 
+    #[test]
+    fn clicks_exclude_the_group() {
+        let (toolbar, first, second) = toolbar();
+        first.radio("tool");
+        second.radio("tool").tooltip("Brush");
+        let changes = record(&toolbar);
+
+        click(&toolbar, &first);
+        click(&toolbar, &second);
+        click(&toolbar, &second);
+        assert!(!first.selected.get());
+        assert!(second.selected.get());
+        assert_eq!(*changes.borrow(), vec!["Pen", "Brush"]);
+    }
+
+    #[test]
+    fn checkable_icons_toggle() {
+        let (toolbar, first, second) = toolbar();
+        first.checkable(true);
+        second.radio("tool");
+        let changes = record(&toolbar);
+
+        click(&toolbar, &second);
+        click(&toolbar, &first);
+        assert!(first.selected.get() && second.selected.get());
+        click(&toolbar, &first);
+        assert!(!first.selected.get());
+        assert_eq!(changes.borrow().len(), 3);
+    }
+}
@@ -0,0 +1,808 @@
+use orbclient::{Color, Renderer};
+use orbimage;
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cell::{CheckSet, CloneCell};
+use event::Event;
+use point::Point;
+use rect::Rect;
+use theme::{BUTTON_FG_SELECTION, ITEM_BACKGROUND, ITEM_BORDER, ITEM_FOREGROUND, ITEM_HOVER, ITEM_SELECTION};
+use traits::{Click, Place, Tooltip};
+use widgets::{Orientation, ScrollBar, Widget};
+use widgets::list::reuse_image;
+use widgets::selection::Selection;
+use widgets::scroll_bar::SCROLL_BAR_SIZE;
+use widgets::table::draw_text;
+
+/// A node of a tree, with child nodes shown below it when it is expanded
+pub struct TreeNode {
+    pub text: CloneCell<String>,
+    pub expanded: Cell<bool>,
+    /// Whether the node can be expanded before it has children, for children loaded when it is expanded
+    pub expandable: Cell<bool>,
+    children: RefCell<Vec<Arc<TreeNode>>>,
+    click_callback: RefCell<Option<Arc<Fn(&TreeNode, Point)>>>,
+}
+
+impl TreeNode {
+    pub fn new<S: Into<String>>(text: S) -> Arc<Self> {
+        Arc::new(TreeNode {
+            text: CloneCell::new(text.into()),
+            expanded: Cell::new(false),
+            expandable: Cell::new(false),
+            children: RefCell::new(vec![]),
+            click_callback: RefCell::new(None),
+        })
+    }
+
+    pub fn text<S: Into<String>>(&self, text: S) -> &Self {
+        self.text.set(text.into());
+        self
+    }
+
+    pub fn expandable(&self, expandable: bool) -> &Self {
+        self.expandable.set(expandable);
+        self
+    }
+
+    /// Adds a child node
+    pub fn add(&self, node: &Arc<TreeNode>) -> &Self {
+        self.children.borrow_mut().push(node.clone());
+        self
+    }
+
+    pub fn remove(&self, node: &Arc<TreeNode>) {
+        self.children.borrow_mut().retain(|other| !Arc::ptr_eq(other, node));
+    }
+
+    pub fn clear(&self) {
+        self.children.borrow_mut().clear();
+    }
+
+    pub fn children(&self) -> Vec<Arc<TreeNode>> {
+        self.children.borrow().clone()
+    }
+
+    pub fn has_children(&self) -> bool {
+        !self.children.borrow().is_empty()
+    }
+
+    /// Whether the node shows an arrow to expand or collapse it
+    fn has_arrow(&self) -> bool {
+        self.expandable.get() || self.has_children()
+    }
+}
+
+impl Click for TreeNode {
+    fn emit_click(&self, point: Point) {
+        if let Some(ref click_callback) = *self.click_callback.borrow() {
+            click_callback(self, point);
+        }
+    }
+
+    fn on_click<T: Fn(&Self, Point) + 'static>(&self, func: T) -> &Self {
+        *self.click_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+}
+
+/// A shown node, with the indentation guides to its left
+struct Row {
+    node: Arc<TreeNode>,
+    depth: usize,
+    /// For each level of indentation, whether a guide line continues below the row
+    guides: Vec<bool>,
+}
+
+/// Path from the roots to a node, excluding the node
+fn path_to(nodes: &[Arc<TreeNode>], node: &Arc<TreeNode>, path: &mut Vec<Arc<TreeNode>>) -> bool {
+    for other in nodes.iter() {
+        if Arc::ptr_eq(other, node) {
+            return true;
+        }
+        path.push(other.clone());
+        if path_to(&other.children.borrow(), node, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// A scrollable tree of nodes, shown one per row and indented by depth
+/// Nodes marked expandable can load their children when they are first expanded,
+/// from the callback set with `TreeView::on_expand`.
+pub struct TreeView {
+    pub rect: Cell<Rect>,
+    pub tooltip_text: CloneCell<String>,
+    pub bg: Color,
+    pub fg: Color,
+    pub fg_guide: Color,
+    pub bg_selection: Color,
+    pub fg_selection: Color,
+    pub bg_hover: Color,
+    roots: RefCell<Vec<Arc<TreeNode>>>,
+    rows: RefCell<Vec<Row>>,
+    pub row_height: Cell<u32>,
+    /// Width of each level of indentation
+    pub indent: Cell<u32>,
+    v_scroll: Cell<i32>,
+    v_scroll_bar: Arc<ScrollBar>,
+    target: RefCell<Option<orbimage::Image>>,
+    /// Selected row
+    selection: Selection,
+    hover: Cell<Option<usize>>,
+    pressed: Cell<bool>,
+    last_press: Cell<Option<(Instant, usize)>>,
+    selection_changed_callback: RefCell<Option<Arc<Fn(&TreeView)>>>,
+    expand_callback: RefCell<Option<Arc<Fn(&TreeView, &Arc<TreeNode>)>>>,
+    pub visible: Cell<bool>,
+}
+
+impl TreeView {
+    pub fn new() -> Arc<Self> {
+        Arc::new(TreeView {
+            rect: Cell::new(Rect::default()),
+            tooltip_text: CloneCell::new(String::new()),
+            bg: ITEM_BACKGROUND,
+            fg: ITEM_FOREGROUND,
+            fg_guide: ITEM_BORDER,
+            bg_selection: ITEM_SELECTION,
+            fg_selection: BUTTON_FG_SELECTION,
+            bg_hover: ITEM_HOVER,
+            roots: RefCell::new(vec![]),
+            rows: RefCell::new(vec![]),
+            row_height: Cell::new(24),
+            indent: Cell::new(16),
+            v_scroll: Cell::new(0),
+            v_scroll_bar: ScrollBar::new(Orientation::Vertical),
+            target: RefCell::new(None),
+            selection: Selection::new(),
+            hover: Cell::new(None),
+            pressed: Cell::new(false),
+            last_press: Cell::new(None),
+            selection_changed_callback: RefCell::new(None),
+            expand_callback: RefCell::new(None),
+            visible: Cell::new(true),
+        })
+    }
+
+    /// Adds a top level node
+    pub fn add(&self, node: &Arc<TreeNode>) -> &Self {
+        self.roots.borrow_mut().push(node.clone());
+        self.update_rows();
+        self
+    }
+
+    pub fn remove(&self, node: &Arc<TreeNode>) {
+        self.roots.borrow_mut().retain(|other| !Arc::ptr_eq(other, node));
+        self.update_rows();
+    }
+
+    /// Remove all nodes
+    pub fn clear(&self) {
+        self.roots.borrow_mut().clear();
+        self.v_scroll.set(0);
+        self.update_rows();
+    }
+
+    pub fn row_height(&self, row_height: u32) -> &Self {
+        self.row_height.set(row_height);
+        self
+    }
+
+    pub fn indent(&self, indent: u32) -> &Self {
+        self.indent.set(indent);
+        self
+    }
+
+    /// Called when a node is about to be expanded, so its children can be loaded
+    /// A node marked expandable that still has no children afterwards loses its arrow.
+    pub fn on_expand<T: Fn(&Self, &Arc<TreeNode>) + 'static>(&self, func: T) -> &Self {
+        *self.expand_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_expand(&self, node: &Arc<TreeNode>) {
+        let callback = self.expand_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self, node);
+        }
+    }
+
+    pub fn expand(&self, node: &Arc<TreeNode>) {
+        if node.expanded.get() {
+            return;
+        }
+
+        self.emit_expand(node);
+        if node.has_children() {
+            node.expanded.set(true);
+        } else {
+            node.expandable.set(false);
+        }
+        self.update_rows();
+    }
+
+    /// Collapse a node, moving the selection to it from any node below it
+    pub fn collapse(&self, node: &Arc<TreeNode>) {
+        if node.expanded.check_set(false) {
+            self.update_rows();
+        }
+    }
+
+    pub fn toggle(&self, node: &Arc<TreeNode>) {
+        if node.expanded.get() {
+            self.collapse(node);
+        } else {
+            self.expand(node);
+        }
+    }
+
+    pub fn on_selection_changed<T: Fn(&Self) + 'static>(&self, func: T) -> &Self {
+        *self.selection_changed_callback.borrow_mut() = Some(Arc::new(func));
+        self
+    }
+
+    pub fn emit_selection_changed(&self) {
+        let callback = self.selection_changed_callback.borrow().clone();
+        if let Some(callback) = callback {
+            callback(self);
+        }
+    }
+
+    pub fn selected(&self) -> Option<Arc<TreeNode>> {
+        self.selected_index().and_then(|i| self.row_node(i))
+    }
+
+    /// Select a node, expanding the nodes above it and scrolling it into view
+    pub fn select(&self, node: &Arc<TreeNode>) {
+        let mut path = vec![];
+        if !path_to(&self.roots.borrow(), node, &mut path) {
+            return;
+        }
+        for parent in path.iter() {
+            if !parent.expanded.get() {
+                self.expand(parent);
+            }
+        }
+
+        if let Some(i) = self.row_index(node) {
+            self.select_row(i);
+        }
+    }
+
+    pub fn clear_selection(&self) {
+        if self.selection.set(Default::default()) {
+            self.emit_selection_changed();
+        }
+    }
+
+    /// Show the changes made to nodes directly, such as children added to an expanded node
+    /// Changes made through the tree view are shown without it.
+    pub fn refresh(&self) {
+        self.update_rows();
+    }
+
+    /// Flatten the expanded part of the tree into rows, keeping the selection on the same node
+    fn update_rows(&self) {
+        let mut rows = vec![];
+        self.add_rows(&mut rows, &self.roots.borrow(), 0, &mut vec![]);
+        let old_rows = self.rows.replace(rows);
+        if self.selection.remap(|i| old_rows.get(i).and_then(|row| self.shown_row(&row.node))) {
+            self.emit_selection_changed();
+        }
+        self.scroll(0);
+    }
+
+    /// Row of a node, or of the collapsed node hiding it
+    fn shown_row(&self, node: &Arc<TreeNode>) -> Option<usize> {
+        let mut path = vec![];
+        if !path_to(&self.roots.borrow(), node, &mut path) {
+            return None;
+        }
+        match path.iter().find(|parent| !parent.expanded.get()) {
+            Some(parent) => self.row_index(parent),
+            None => self.row_index(node),
+        }
+    }
+
+    fn add_rows(&self, rows: &mut Vec<Row>, nodes: &[Arc<TreeNode>], depth: usize, guides: &mut Vec<bool>) {
+        for (i, node) in nodes.iter().enumerate() {
+            // Top level nodes have no guide line to their left
+            let more = i + 1 < nodes.len();
+            let mut row_guides = guides.clone();
+            if depth > 0 {
+                row_guides.push(more);
+            }
+            rows.push(Row {
+                node: node.clone(),
+                depth: depth,
+                guides: row_guides,
+            });
+
+            if node.expanded.get() {
+                if depth > 0 {
+                    guides.push(more);
+                }
+                self.add_rows(rows, &node.children.borrow(), depth + 1, guides);
+                if depth > 0 {
+                    guides.pop();
+                }
+            }
+        }
+    }
+
+    fn row_index(&self, node: &Arc<TreeNode>) -> Option<usize> {
+        self.rows.borrow().iter().position(|row| Arc::ptr_eq(&row.node, node))
+    }
+
+    fn row_node(&self, i: usize) -> Option<Arc<TreeNode>> {
+        self.rows.borrow().get(i).map(|row| row.node.clone())
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.selection.keys().first().cloned()
+    }
+
+    /// Select a shown row and scroll it into view
+    fn select_row(&self, i: usize) {
+        if i < self.rows.borrow().len() {
+            if self.selection.select(i, i) {
+                self.emit_selection_changed();
+            }
+            self.scroll_to_row(i);
+        }
+    }
+
+    /// Index of the shown parent of a row
+    fn parent_row(&self, i: usize) -> Option<usize> {
+        let rows = self.rows.borrow();
+        let depth = rows.get(i)?.depth;
+        (0..i).rev().find(|&j| rows[j].depth < depth)
+    }
+
+    fn content_height(&self) -> u32 {
+        self.rows.borrow().len() as u32 * self.row_height.get()
+    }
+
+    pub fn scroll(&self, y: i32) {
+        let max = cmp::max(0, self.content_height() as i32 - self.rect.get().height as i32);
+        self.v_scroll.set(cmp::max(0, cmp::min(max, self.v_scroll.get() + y)));
+    }
+
+    fn scroll_to_row(&self, i: usize) {
+        let h = self.row_height.get() as i32;
+        let y = i as i32 * h;
+        let v_scroll = self.v_scroll.get();
+        let height = self.rect.get().height as i32;
+
+        if y < v_scroll {
+            self.scroll(y - v_scroll);
+        } else if y + h > v_scroll + height {
+            self.scroll(y + h - (v_scroll + height));
+        }
+    }
+
+    /// Indexes of the rows overlapping a vertical range, relative to the top of the tree
+    fn rows_between(&self, top: i32, bottom: i32) -> Range<usize> {
+        let h = cmp::max(1, self.row_height.get()) as i32;
+        let len = self.rows.borrow().len();
+        let start = cmp::max(0, top / h) as usize;
+        let end = cmp::max(0, (bottom + h - 1) / h) as usize;
+        cmp::min(start, len)..cmp::min(end, len)
+    }
+
+    fn row_at(&self, point: Point) -> Option<usize> {
+        let rect = self.rect.get();
+        if rect.contains(point) && point.x < rect.x + self.row_width() as i32 {
+            let y = point.y - rect.y + self.v_scroll.get();
+            let range = self.rows_between(y, y + 1);
+            if range.start < range.end {
+                return Some(range.start);
+            }
+        }
+        None
+    }
+
+    /// Whether a point is on the expand arrow of a row
+    fn on_arrow(&self, i: usize, point: Point) -> bool {
+        let rows = self.rows.borrow();
+        match rows.get(i) {
+            Some(row) => {
+                let indent = self.indent.get() as i32;
+                let x = self.rect.get().x + 4 + row.depth as i32 * indent;
+                row.node.has_arrow() && point.x >= x && point.x < x + indent
+            },
+            None => false,
+        }
+    }
+
+    fn has_scroll_bar(&self) -> bool {
+        self.content_height() > self.rect.get().height
+    }
+
+    /// Width left to the rows next to the scroll bar
+    fn row_width(&self) -> u32 {
+        let width = self.rect.get().width;
+        if self.has_scroll_bar() {
+            cmp::max(width, SCROLL_BAR_SIZE) - SCROLL_BAR_SIZE
+        } else {
+            width
+        }
+    }
+
+    fn update_scroll_bar(&self) {
+        let rect = self.rect.get();
+        self.v_scroll_bar.rect.set(Rect::new(
+            rect.x + rect.width as i32 - SCROLL_BAR_SIZE as i32, rect.y,
+            SCROLL_BAR_SIZE, rect.height
+        ));
+        self.v_scroll_bar.range(self.content_height(), rect.height).value(self.v_scroll.get());
+    }
+
+    fn draw_row(&self, target: &mut Renderer, row: &Row, y: i32, width: u32, selected: bool, hover: bool) {
+        let h = self.row_height.get();
+        let indent = self.indent.get() as i32;
+        let mid = y + h as i32 / 2;
+
+        let fg = if selected {
+            target.rect(0, y, width, h, self.bg_selection);
+            self.fg_selection
+        } else {
+            if hover {
+                target.rect(0, y, width, h, self.bg_hover);
+            }
+            self.fg
+        };
+
+        // Guides run down from each parent, with a tick to the row on the last level
+        for (level, &more) in row.guides.iter().enumerate() {
+            let guide_x = 4 + level as i32 * indent + indent / 2;
+            if level + 1 < row.guides.len() {
+                if more {
+                    target.rect(guide_x, y, 1, h, self.fg_guide);
+                }
+            } else {
+                let bottom = if more { h } else { h / 2 + 1 };
+                target.rect(guide_x, y, 1, bottom, self.fg_guide);
+                let tick = if row.node.has_arrow() { indent / 2 - 2 } else { indent / 2 + 4 };
+                target.rect(guide_x, mid, tick as u32, 1, self.fg_guide);
+            }
+        }
+
+        let x = 4 + row.depth as i32 * indent;
+        if row.node.has_arrow() {
+            let center_x = x + indent / 2;
+            for i in 0..5 {
+                if row.node.expanded.get() {
+                    target.rect(center_x - 4 + i, mid - 2 + i, (9 - 2 * i) as u32, 1, fg);
+                } else {
+                    target.rect(center_x - 2 + i, mid - 4 + i, 1, (9 - 2 * i) as u32, fg);
+                }
+            }
+        }
+
+        let text_x = x + indent + 2;
+        draw_text(target, text_x, y + (h as i32 - 16) / 2, width as i32 - text_x, &row.node.text.get(), fg);
+    }
+}
+
+impl Tooltip for TreeView {}
+
+impl Widget for TreeView {
+    fn rect(&self) -> &Cell<Rect> {
+        &self.rect
+    }
+
+    fn tooltip_text(&self) -> Option<&CloneCell<String>> {
+        Some(&self.tooltip_text)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    fn draw(&self, renderer: &mut Renderer, focused: bool) {
+        if self.visible.get() {
+            let rect = self.rect.get();
+            let v_scroll = self.v_scroll.get();
+            let row_width = self.row_width();
+            let h = self.row_height.get() as i32;
+            let selected = self.selected_index();
+
+            let mut target = self.target.borrow_mut();
+            let target = reuse_image(&mut target, rect.width, rect.height);
+            target.set(self.bg);
+
+            // Only the visible rows are drawn
+            for i in self.rows_between(v_scroll, v_scroll + rect.height as i32) {
+                let rows = self.rows.borrow();
+                self.draw_row(target, &rows[i], i as i32 * h - v_scroll, row_width,
+                              selected == Some(i), self.hover.get() == Some(i));
+            }
+
+            // Outline the selection when the tree has the keyboard focus
+            if focused {
+                if let Some(i) = selected {
+                    let y = i as i32 * h - v_scroll;
+                    target.rect(0, y, row_width, 1, self.fg_guide);
+                    target.rect(0, y + h - 1, row_width, 1, self.fg_guide);
+                }
+            }
+
+            renderer.image(rect.x, rect.y, rect.width, rect.height, &target.data());
+
+            if self.has_scroll_bar() {
+                self.update_scroll_bar();
+                self.v_scroll_bar.draw(renderer, false);
+            }
+        }
+    }
+
+    fn event(&self, event: Event, mut focused: bool, redraw: &mut bool) -> bool {
+        if !self.visible.get() {
+            return focused;
+        }
+
+        match event {
+            Event::Mouse { point, left_button, .. } => {
+                if self.has_scroll_bar() {
+                    self.update_scroll_bar();
+                    if self.v_scroll_bar.event(event, false, redraw) {
+                        self.v_scroll.set(self.v_scroll_bar.value.get());
+                        if self.hover.check_set(None) {
+                            *redraw = true;
+                        }
+                        return focused;
+                    }
+                }
+
+                let mut press = false;
+                let mut click = false;
+                if left_button {
+                    if self.rect.get().contains(point) && self.pressed.check_set(true) {
+                        press = true;
+                    }
+                } else if self.pressed.check_set(false) && self.rect.get().contains(point) {
+                    click = true;
+                }
+
+                let index = self.row_at(point);
+                if self.hover.check_set(index) {
+                    *redraw = true;
+                }
+
+                if press {
+                    focused = true;
+                    *redraw = true;
+                }
+
+                if let Some(i) = index {
+                    let node = self.row_node(i).unwrap();
+                    if press {
+                        // Clicking the arrow or double clicking the row expands or collapses it
+                        let double = match self.last_press.get() {
+                            Some((time, last_i)) => last_i == i && time.elapsed() < Duration::from_millis(400),
+                            None => false,
+                        };
+                        if self.on_arrow(i, point) {
+                            self.last_press.set(None);
+                            self.toggle(&node);
+                        } else {
+                            self.select_row(i);
+                            if double {
+                                self.last_press.set(None);
+                                self.toggle(&node);
+                            } else {
+                                self.last_press.set(Some((Instant::now(), i)));
+                            }
+                        }
+                    }
+
+                    if click && !self.on_arrow(i, point) {
+                        node.emit_click(point);
+                    }
+                }
+            },
+            Event::UpArrow if focused => {
+                match self.selected_index() {
+                    Some(i) => if i > 0 { self.select_row(i - 1) },
+                    None => self.select_row(0),
+                }
+                *redraw = true;
+            },
+            Event::DownArrow if focused => {
+                match self.selected_index() {
+                    Some(i) => self.select_row(i + 1),
+                    None => self.select_row(0),
+                }
+                *redraw = true;
+            },
+            Event::Home if focused => {
+                self.select_row(0);
+                *redraw = true;
+            },
+            Event::End if focused => {
+                let len = self.rows.borrow().len();
+                if len > 0 {
+                    self.select_row(len - 1);
+                }
+                *redraw = true;
+            },
+            Event::RightArrow if focused => {
+                // Expand the node, or move to its first child when it is already expanded
+                if let Some(i) = self.selected_index() {
+                    let node = self.row_node(i).unwrap();
+                    if node.expanded.get() {
+                        self.select_row(i + 1);
+                    } else if node.has_arrow() {
+                        self.expand(&node);
+                    }
+                    *redraw = true;
+                }
+            },
+            Event::LeftArrow if focused => {
+                // Collapse the node, or move to its parent when it is already collapsed
+                if let Some(i) = self.selected_index() {
+                    let node = self.row_node(i).unwrap();
+                    if node.expanded.get() {
+                        self.collapse(&node);
+                    } else if let Some(parent) = self.parent_row(i) {
+                        self.select_row(parent);
+                    }
+                    *redraw = true;
+                }
+            },
+            Event::Enter if focused => {
+                if let Some(node) = self.selected() {
+                    node.emit_click(Point::new(0, 0));
+                }
+            },
+            Event::Scroll { y, .. } if self.hover.get().is_some() || focused => {
+                self.scroll(y * -96);
+                *redraw = true;
+            },
+            _ => (),
+        }
+
+        focused
+    }
+
+    fn visible(&self, flag: bool) {
+        self.visible.set(flag);
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("TreeView")
+    }
+}
+
+impl Place for TreeView {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use event::Event;
+    use traits::Place;
+    use widgets::Widget;
+    use super::{TreeNode, TreeView};
+
+    /// A tree of files: src holding widgets and lib.rs, widgets holding two files, and Cargo.toml
+    fn tree() -> (Arc<TreeView>, Vec<Arc<TreeNode>>) {
+        let nodes: Vec<Arc<TreeNode>> = ["src", "widgets", "list.rs", "tree_view.rs", "lib.rs", "Cargo.toml"]
+            .iter().map(|&text| TreeNode::new(text)).collect();
+        nodes[1].add(&nodes[2]).add(&nodes[3]);
+        nodes[0].add(&nodes[1]).add(&nodes[4]);
+
+        let tree_view = TreeView::new();
+        tree_view.size(200, 200);
+        tree_view.add(&nodes[0]).add(&nodes[5]);
+        (tree_view, nodes)
+    }
+
+    fn shown(tree_view: &TreeView) -> Vec<String> {
+        tree_view.rows.borrow().iter().map(|row| row.node.text.get()).collect()
+    }
+
+    fn key(tree_view: &TreeView, event: Event) {
+        let mut redraw = false;
+        tree_view.event(event, true, &mut redraw);
+    }
+
+    #[test]
+    fn rows_show_expanded_nodes_with_guides() {
+        let (tree_view, nodes) = tree();
+        assert_eq!(shown(&tree_view), vec!["src", "Cargo.toml"]);
+
+        tree_view.expand(&nodes[0]);
+        tree_view.expand(&nodes[1]);
+        assert_eq!(shown(&tree_view), vec!["src", "widgets", "list.rs", "tree_view.rs", "lib.rs", "Cargo.toml"]);
+
+        let rows = tree_view.rows.borrow();
+        let depths: Vec<usize> = rows.iter().map(|row| row.depth).collect();
+        assert_eq!(depths, vec![0, 1, 2, 2, 1, 0]);
+        // The guide of widgets continues down to lib.rs, the last child of src
+        let guides: Vec<Vec<bool>> = rows.iter().map(|row| row.guides.clone()).collect();
+        assert_eq!(guides, vec![vec![], vec![true], vec![true, true], vec![true, false], vec![false], vec![]]);
+    }
+
+    #[test]
+    fn parent_rows() {
+        let (tree_view, nodes) = tree();
+        tree_view.select(&nodes[3]);
+        assert_eq!(tree_view.parent_row(3), Some(1));
+        assert_eq!(tree_view.parent_row(4), Some(0));
+        assert_eq!(tree_view.parent_row(1), Some(0));
+        assert_eq!(tree_view.parent_row(0), None);
+        assert_eq!(tree_view.parent_row(5), None);
+        assert_eq!(tree_view.parent_row(6), None);
+    }
+
+    #[test]
+    fn arrow_keys_expand_and_collapse() {
+        let (tree_view, nodes) = tree();
+        key(&tree_view, Event::DownArrow);
+        key(&tree_view, Event::RightArrow);
+        assert!(nodes[0].expanded.get());
+        key(&tree_view, Event::RightArrow);
+        assert!(Arc::ptr_eq(&tree_view.selected().unwrap(), &nodes[1]));
+
+        // Left moves to the parent of a collapsed node, then collapses it
+        key(&tree_view, Event::LeftArrow);
+        assert!(Arc::ptr_eq(&tree_view.selected().unwrap(), &nodes[0]));
+        key(&tree_view, Event::LeftArrow);
+        assert!(!nodes[0].expanded.get());
+        assert_eq!(shown(&tree_view), vec!["src", "Cargo.toml"]);
+
+        // Leaves don't expand
+        key(&tree_view, Event::DownArrow);
+        key(&tree_view, Event::RightArrow);
+        assert!(!nodes[5].expanded.get());
+    }
+
+    #[test]
+    fn expandable_nodes_load_their_children() {
+        let tree_view = TreeView::new();
+        let lazy = TreeNode::new("lazy");
+        lazy.expandable(true);
+        let empty = TreeNode::new("empty");
+        empty.expandable(true);
+        tree_view.add(&lazy).add(&empty);
+        tree_view.on_expand(|_, node| {
+            if node.text.get() == "lazy" {
+                node.add(&TreeNode::new("loaded"));
+            }
+        });
+
+        tree_view.expand(&lazy);
+        assert!(lazy.expanded.get());
+        tree_view.expand(&empty);
+        assert!(!empty.expanded.get());
+        assert!(!empty.has_arrow());
+        assert_eq!(shown(&tree_view), vec!["lazy", "loaded", "empty"]);
+    }
+
+    #[test]
+    fn selection_moves_to_the_collapsed_node() {
+        let (tree_view, nodes) = tree();
+        tree_view.select(&nodes[2]);
+        assert_eq!(tree_view.selected_index(), Some(2));
+
+        tree_view.collapse(&nodes[0]);
+        assert!(Arc::ptr_eq(&tree_view.selected().unwrap(), &nodes[0]));
+        // The selection stays on the node when it moves down
+        tree_view.expand(&nodes[0]);
+        assert!(Arc::ptr_eq(&tree_view.selected().unwrap(), &nodes[0]));
+
+        // A removed node is no longer selected
+        tree_view.select(&nodes[5]);
+        tree_view.remove(&nodes[5]);
+        assert!(tree_view.selected().is_none());
+    }
+}
@@ -3,11 +3,15 @@ extern crate orbfont;
 use orbclient::{self, Renderer, WindowFlag};
 use orbclient::color::Color;
 use std::cell::{Cell, RefCell};
+use std::cmp::max;
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use super::{Event, Point, Rect, Widget};
-use theme::WINDOW_BACKGROUND;
+use super::{Accelerator, Event, Overlay, Point, Rect, Widget};
+use input_method::InputMethod;
+use theme::{TOOLTIP_BACKGROUND, TOOLTIP_BORDER, TOOLTIP_FOREGROUND, WINDOW_BACKGROUND};
 use traits::Resize;
 
 pub use orbclient::Window as InnerWindow;
@@ -68,10 +72,21 @@ pub struct Window {
     pub bg: Cell<Color>,
     pub running: Cell<bool>,
     resize_callback: RefCell<Option<Arc<Fn(&Window, u32, u32)>>>,
+    input_method: RefCell<Option<Box<InputMethod>>>,
+    /// How long the mouse rests over a widget before its tooltip is shown
+    pub tooltip_delay: Cell<Duration>,
+    /// Where the mouse rests, which is where the tooltip is shown
+    tooltip_point: Point,
+    /// Since when the mouse rests, while waiting to show a tooltip
+    tooltip_rest: Option<Instant>,
+    tooltip: Option<String>,
     mouse_point: Point,
     mouse_left: bool,
     mouse_middle: bool,
     mouse_right: bool,
+    key_ctrl: bool,
+    key_shift: bool,
+    key_alt: bool,
     events: VecDeque<Event>,
     redraw: bool,
 }
@@ -114,10 +129,18 @@ impl Window {
             bg: Cell::new(WINDOW_BACKGROUND),
             running: Cell::new(true),
             resize_callback: RefCell::new(None),
+            input_method: RefCell::new(None),
+            tooltip_delay: Cell::new(Duration::from_millis(700)),
+            tooltip_point: Point::new(0, 0),
+            tooltip_rest: None,
+            tooltip: None,
             mouse_point: Point::new(0, 0),
             mouse_left: false,
             mouse_right: false,
             mouse_middle: false,
+            key_ctrl: false,
+            key_shift: false,
+            key_alt: false,
             events: events,
             redraw: true,
         }
@@ -201,6 +224,49 @@ impl Window {
     
     }
 
+    /// Accelerators bound to more than one action, with the texts of those actions
+    pub fn accelerator_conflicts(&self) -> Vec<(Accelerator, Vec<String>)> {
+        let mut bindings: Vec<(Accelerator, Vec<String>)> = Vec::new();
+        for widget in self.widgets.borrow().iter() {
+            for (accelerator, text) in widget.accelerators() {
+                match bindings.iter().position(|&(other, _)| other == accelerator) {
+                    Some(i) => bindings[i].1.push(text),
+                    None => bindings.push((accelerator, vec![text])),
+                }
+            }
+        }
+        bindings.retain(|&(_, ref texts)| texts.len() > 1);
+        bindings
+    }
+
+    /// Route typed characters through an input method before they reach widgets
+    pub fn input_method<T: InputMethod + 'static>(&self, input_method: T) {
+        *self.input_method.borrow_mut() = Some(Box::new(input_method));
+    }
+
+    /// Queue an event, to be handled on the next call to drain_events
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    /// Queue a typed character, passing it through the input method if there is one
+    pub fn push_char(&mut self, c: char) {
+        queue_char(self.input_method.get_mut(), &mut self.events, c, false, false, false);
+    }
+
+    /// Queue a key event that is not text, ending any composition
+    fn push_key(&mut self, event: Event) {
+        self.reset_input_method();
+        self.events.push_back(event);
+    }
+
+    /// Abandon the composition of the input method, if there is one
+    fn reset_input_method(&mut self) {
+        if let Some(ref mut input_method) = *self.input_method.borrow_mut() {
+            input_method.reset(&mut self.events);
+        }
+    }
+
     pub fn draw(&self) {
         let mut inner = self.inner.borrow_mut();
         inner.set(self.bg.get());
@@ -211,15 +277,66 @@ impl Window {
                 widget.draw(&mut renderer, self.widget_focus.get() == i);
             }
         }
+
+        // Overlays are drawn above all widgets
+        for widget in self.widgets.borrow().iter() {
+            widget.draw_overlay(&mut renderer);
+        }
+
+        if let Some(ref text) = self.tooltip {
+            draw_tooltip(&mut renderer, text, self.tooltip_point);
+        }
     }
 
     pub fn step(&mut self) {
         self.drain_orbital_events();
         self.drain_events();
+        self.update_tooltip();
+    }
+
+    /// Show the tooltip of the widget under the mouse, once it has rested for the tooltip delay
+    fn update_tooltip(&mut self) {
+        match self.tooltip_rest {
+            Some(time) if time.elapsed() >= self.tooltip_delay.get() => self.tooltip_rest = None,
+            _ => return,
+        }
+        let point = self.tooltip_point;
+
+        let widgets = self.widgets.borrow().clone();
+        // No tooltips are shown over an open menu
+        if widgets.iter().any(|widget| widget.overlay() == Some(Overlay::Modal)) {
+            return;
+        }
+        if let Some(text) = widgets.iter().rev().filter_map(|widget| widget.tooltip_at(point)).next() {
+            self.tooltip = Some(text);
+            self.redraw = true;
+        }
+    }
+
+    /// Hide the tooltip on clicks and keys, and wait again for the mouse to rest when it moves
+    fn tooltip_event(&mut self, event: Event) {
+        match event {
+            Event::Init | Event::Resize { .. } => return,
+            Event::Mouse { point, left_button: false, middle_button: false, right_button: false } => {
+                // A release where the mouse was clicked does not show the tooltip again
+                if point == self.tooltip_point {
+                    return;
+                }
+                self.tooltip_point = point;
+                self.tooltip_rest = Some(Instant::now());
+            },
+            _ => self.tooltip_rest = None,
+        }
+
+        if self.tooltip.take().is_some() {
+            self.redraw = true;
+        }
     }
 
     pub fn drain_events(&mut self) {
         while let Some(event) = self.events.pop_front() {
+            self.tooltip_event(event);
+
             match event {
                 Event::Resize { width, height } => {
                     self.emit_resize(width, height);
@@ -227,24 +344,58 @@ impl Window {
                 _ => ()
             }
 
-            //reversed order here to give priority to menu widgets as they are added usually at last to the window
-            for i in (0..self.widgets.borrow().len()).rev() {
-                if let Some(widget) = self.widgets.borrow().get(i) {
-                    if widget.event(event, self.widget_focus.get() == i, &mut self.redraw) {
-                        if self.widget_focus.get() != i {
-                            self.widget_focus.set(i);
-                            self.redraw = true;
+            if let Some(accelerator) = Accelerator::from_event(&event) {
+                let widgets = self.widgets.borrow().clone();
+                if accelerate(&widgets, accelerator) {
+                    self.redraw = true;
+                    continue;
+                }
+            }
+
+            // Widgets showing an overlay get events first, and a modal one keeps them from the others
+            let len = self.widgets.borrow().len();
+            let mut handled = vec![false; len];
+            let mut modal = false;
+            for i in (0..len).rev() {
+                let overlay = self.widgets.borrow()[i].overlay();
+                if let Some(overlay) = overlay {
+                    self.widget_event(i, event);
+                    handled[i] = true;
+                    if overlay == Overlay::Modal {
+                        modal = true;
+                        break;
+                    }
+                }
+            }
+
+            if !modal {
+                //reversed order here to give priority to widgets added last, which are drawn on top
+                for i in (0..len).rev() {
+                    if !handled[i] {
+                        self.widget_event(i, event);
+                        // A widget opening a modal overlay, like a menu, keeps the event from the widgets under it
+                        if self.widgets.borrow()[i].overlay() == Some(Overlay::Modal) {
+                            break;
                         }
                     }
-                    //if widget Menu is activated then break to avoid clicking also on widgets under the unfolded menu
-                    if widget.name().unwrap() == "MenuActivated" {break;} 
                 }
             }
         }
     }
 
+    fn widget_event(&mut self, i: usize, event: Event) {
+        let widget = self.widgets.borrow()[i].clone();
+        if widget.event(event, self.widget_focus.get() == i, &mut self.redraw) {
+            if self.widget_focus.get() != i {
+                self.widget_focus.set(i);
+                self.redraw = true;
+            }
+        }
+    }
+
     pub fn drain_orbital_events(&mut self) {
-        for orbital_event in self.inner.borrow_mut().events() {
+        let orbital_events: Vec<orbclient::Event> = self.inner.borrow_mut().events().collect();
+        for orbital_event in orbital_events {
             match orbital_event.to_option() {
                 orbclient::EventOption::Mouse(mouse_event) => {
                     self.mouse_point.x = mouse_event.x;
@@ -258,6 +409,9 @@ impl Window {
                     })
                 },
                 orbclient::EventOption::Button(button_event) => {
+                    // A click may move the focus, so a composition must not carry over to another widget
+                    self.reset_input_method();
+
                     self.mouse_left = button_event.left;
                     self.mouse_middle = button_event.middle;
                     self.mouse_right = button_event.right;
@@ -276,22 +430,48 @@ impl Window {
                     })
                 },
                 orbclient::EventOption::Key(key_event) => {
-                    if key_event.pressed {
+                    let modifier = match key_event.scancode {
+                        orbclient::K_CTRL => { self.key_ctrl = key_event.pressed; true },
+                        orbclient::K_LEFT_SHIFT | orbclient::K_RIGHT_SHIFT => { self.key_shift = key_event.pressed; true },
+                        orbclient::K_ALT => { self.key_alt = key_event.pressed; true },
+                        _ => false,
+                    };
+
+                    if modifier {
+                        self.events.push_back(Event::Modifiers {
+                            ctrl: self.key_ctrl,
+                            shift: self.key_shift,
+                            alt: self.key_alt,
+                        });
+                    } else if key_event.pressed {
                         match key_event.scancode {
-                            orbclient::K_BKSP => self.events.push_back(Event::Backspace),
-                            orbclient::K_DEL => self.events.push_back(Event::Delete),
-                            orbclient::K_HOME => self.events.push_back(Event::Home),
-                            orbclient::K_END => self.events.push_back(Event::End),
-                            orbclient::K_UP => self.events.push_back(Event::UpArrow),
-                            orbclient::K_DOWN => self.events.push_back(Event::DownArrow),
-                            orbclient::K_LEFT => self.events.push_back(Event::LeftArrow),
-                            orbclient::K_RIGHT => self.events.push_back(Event::RightArrow),
+                            orbclient::K_ESC => self.push_key(Event::Escape),
+                            orbclient::K_BKSP => self.push_key(Event::Backspace),
+                            orbclient::K_DEL => self.push_key(Event::Delete),
+                            orbclient::K_HOME => self.push_key(Event::Home),
+                            orbclient::K_END => self.push_key(Event::End),
+                            orbclient::K_UP => self.push_key(Event::UpArrow),
+                            orbclient::K_DOWN => self.push_key(Event::DownArrow),
+                            orbclient::K_LEFT => self.push_key(Event::LeftArrow),
+                            orbclient::K_RIGHT => self.push_key(Event::RightArrow),
+                            orbclient::K_F1 ..= orbclient::K_F10 | orbclient::K_F11 | orbclient::K_F12 => {
+                                let n = match key_event.scancode {
+                                    orbclient::K_F11 => 11,
+                                    orbclient::K_F12 => 12,
+                                    scancode => scancode - orbclient::K_F1 + 1,
+                                };
+                                let (ctrl, shift, alt) = (self.key_ctrl, self.key_shift, self.key_alt);
+                                self.push_key(Event::Function { n: n, ctrl: ctrl, shift: shift, alt: alt })
+                            },
                             _ => {
                                 match key_event.character {
                                     '\0' => (),
                                     '\x1B' => (),
-                                    '\n' => self.events.push_back(Event::Enter),
-                                    _ => self.events.push_back(Event::Text { c: key_event.character }),
+                                    '\n' => self.push_key(Event::Enter),
+                                    c => {
+                                        let (ctrl, shift, alt) = (self.key_ctrl, self.key_shift, self.key_alt);
+                                        queue_char(self.input_method.get_mut(), &mut self.events, c, ctrl, shift, alt)
+                                    },
                                 }
                             }
                         }
@@ -316,8 +496,22 @@ impl Window {
     pub fn exec(&mut self) {
         'event: while self.running.get() {
             self.drain_events();
+            self.update_tooltip();
             self.draw_if_needed();
-            self.drain_orbital_events();
+            self.wait_orbital_events();
+        }
+    }
+
+    /// Wait for events, waking up while a tooltip waits for its delay so it shows without further input
+    fn wait_orbital_events(&mut self) {
+        let poll = self.tooltip_rest.is_some() && !self.inner.borrow().is_async();
+        if poll {
+            self.inner.borrow_mut().set_async(true);
+            thread::sleep(Duration::from_millis(10));
+        }
+        self.drain_orbital_events();
+        if poll {
+            self.inner.borrow_mut().set_async(false);
         }
     }
 
@@ -332,3 +526,147 @@ impl Window {
         }
     }
 }
+
+/// Run the action bound to an accelerator, returning true if there is one
+/// Accelerators run their actions even when the menus holding them are closed, but while a
+/// modal overlay like an open menu is shown, only the widget showing it gets them.
+fn accelerate(widgets: &[Arc<Widget>], accelerator: Accelerator) -> bool {
+    match widgets.iter().rev().find(|widget| widget.overlay() == Some(Overlay::Modal)) {
+        Some(widget) => widget.accelerate(accelerator),
+        None => widgets.iter().rev().any(|widget| widget.accelerate(accelerator)),
+    }
+}
+
+/// Queue a character typed with the given modifiers, as a shortcut or as text
+/// AltGr, which SDL reports as Alt and Windows as Ctrl+Alt, types symbols like '@' and '{',
+/// so only letters and digits typed with Alt are shortcuts.
+fn queue_char(input_method: &mut Option<Box<InputMethod>>, events: &mut VecDeque<Event>, c: char, ctrl: bool, shift: bool, alt: bool) {
+    let shortcut = if alt { c.is_ascii_alphanumeric() } else { ctrl };
+    if shortcut {
+        if let Some(ref mut input_method) = *input_method {
+            input_method.reset(events);
+        }
+        events.push_back(Event::Shortcut { c: c, ctrl: ctrl, shift: shift, alt: alt });
+    } else {
+        let handled = match *input_method {
+            Some(ref mut input_method) => input_method.key(c, events),
+            None => false,
+        };
+        if !handled {
+            events.push_back(Event::Text { c: c });
+        }
+    }
+}
+
+/// Draw a tooltip below the mouse, moving it to stay inside the window
+fn draw_tooltip(renderer: &mut Renderer, text: &str, point: Point) {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines.iter().map(|line| line.chars().count() as i32).max().unwrap_or(0) * 8 + 8;
+    let height = lines.len() as i32 * 16 + 4;
+
+    let mut x = point.x;
+    let mut y = point.y + 20;
+    if x + width > renderer.width() as i32 {
+        x = renderer.width() as i32 - width;
+    }
+    if y + height > renderer.height() as i32 {
+        y = point.y - height - 4;
+    }
+    x = max(0, x);
+    y = max(0, y);
+
+    renderer.rect(x, y, width as u32, height as u32, TOOLTIP_BACKGROUND);
+    renderer.rect(x, y, width as u32, 1, TOOLTIP_BORDER);
+    renderer.rect(x, y + height - 1, width as u32, 1, TOOLTIP_BORDER);
+    renderer.rect(x, y, 1, height as u32, TOOLTIP_BORDER);
+    renderer.rect(x + width - 1, y, 1, height as u32, TOOLTIP_BORDER);
+
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            renderer.char(x + 4 + col as i32 * 8, y + 2 + row as i32 * 16, c, TOOLTIP_FOREGROUND);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    use accelerator::Accelerator;
+    use event::Event;
+    use input_method::{DeadKeys, InputMethod};
+    use traits::Click;
+    use widgets::{Action, Menu, Widget};
+    use super::{accelerate, queue_char};
+
+    fn typed(events: &VecDeque<Event>) -> Vec<String> {
+        events.iter().map(|event| match *event {
+            Event::Text { c } => c.to_string(),
+            Event::Shortcut { c, ctrl, alt, .. } => format!("{}{}{}", if ctrl { "Ctrl+" } else { "" }, if alt { "Alt+" } else { "" }, c),
+            _ => format!("{:?}", event),
+        }).collect()
+    }
+
+    #[test]
+    fn alt_gr_symbols_are_text() {
+        let mut input_method = None;
+        let mut events = VecDeque::new();
+        // AltGr as SDL reports it, then as Windows reports it
+        queue_char(&mut input_method, &mut events, '@', false, false, true);
+        queue_char(&mut input_method, &mut events, '{', true, false, true);
+        queue_char(&mut input_method, &mut events, '\u{20ac}', false, false, true);
+        assert_eq!(typed(&events), vec!["@", "{", "\u{20ac}"]);
+    }
+
+    #[test]
+    fn modified_letters_are_shortcuts() {
+        let mut input_method = None;
+        let mut events = VecDeque::new();
+        queue_char(&mut input_method, &mut events, 'f', false, false, true);
+        queue_char(&mut input_method, &mut events, 's', true, false, false);
+        queue_char(&mut input_method, &mut events, '+', true, false, false);
+        queue_char(&mut input_method, &mut events, 'a', false, false, false);
+        assert_eq!(typed(&events), vec!["Alt+f", "Ctrl+s", "Ctrl++", "a"]);
+    }
+
+    #[test]
+    fn shortcuts_end_a_composition() {
+        let mut input_method: Option<Box<InputMethod>> = Some(Box::new(DeadKeys::new()));
+        let mut events = VecDeque::new();
+        queue_char(&mut input_method, &mut events, '^', false, false, false);
+        queue_char(&mut input_method, &mut events, 'z', true, false, false);
+        assert_eq!(typed(&events), vec!["Preedit { c: '^' }", "PreeditClear", "Ctrl+z"]);
+    }
+
+    /// A menu with an action bound to an accelerator, counting how often it runs
+    fn menu(accelerator: &str, runs: &Arc<Cell<u32>>) -> Arc<Menu> {
+        let action = Action::new("Action");
+        action.accelerator(accelerator).unwrap();
+        let runs = runs.clone();
+        action.on_click(move |_, _| runs.set(runs.get() + 1));
+        let menu = Menu::new("Menu");
+        menu.add(&action);
+        menu
+    }
+
+    #[test]
+    fn open_menus_keep_accelerators_to_themselves() {
+        let (file_runs, edit_runs) = (Arc::new(Cell::new(0)), Arc::new(Cell::new(0)));
+        let file = menu("Ctrl+S", &file_runs);
+        let edit = menu("Ctrl+Z", &edit_runs);
+        let widgets: Vec<Arc<Widget>> = vec![file.clone(), edit.clone()];
+        let save = Accelerator::parse("Ctrl+S").unwrap();
+        let undo = Accelerator::parse("Ctrl+Z").unwrap();
+
+        assert!(accelerate(&widgets, save));
+        assert_eq!(file_runs.get(), 1);
+
+        edit.open(false);
+        assert!(!accelerate(&widgets, save));
+        assert_eq!(file_runs.get(), 1);
+        assert!(accelerate(&widgets, undo));
+        assert_eq!(edit_runs.get(), 1);
+    }
+}